@@ -0,0 +1,635 @@
+use crate::canvas::{Canvas, Cell};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+use tui::style::Color;
+
+/// Width of the screens ANSI art is drawn for, where lines wrap
+pub const ANSI_WIDTH: usize = 80;
+
+/// Time between the frames of an ANSImation
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The speeds an animation plays at, from slow motion to fast forward
+pub const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Characters 128 to 255 of code page 437, the encoding of DOS-era art
+const CP437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', ' ', //
+];
+
+/// Whether a file is ANSI or ASCII art rather than an image, going by its extension
+pub fn is_art(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("ans") || e.eq_ignore_ascii_case("asc"))
+}
+
+/// Reads an art file into its frames, several for an ANSImation. `.ans` files
+/// wrap at the width of a DOS screen, other text only at its line breaks.
+pub fn load(path: &Path) -> Result<Vec<Canvas>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let wrap = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("ans"))
+        .then_some(ANSI_WIDTH);
+
+    let frames = parse(&decode(&bytes), wrap).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if frames.is_empty() {
+        return Err(format!("{}: the file is empty", path.display()));
+    }
+    Ok(frames)
+}
+
+/// Text as is when it is UTF-8, or else taken as code page 437. The SAUCE
+/// record some art ends with comes after an end-of-file character and is left out.
+fn decode(bytes: &[u8]) -> String {
+    let bytes = match bytes.iter().position(|b| *b == 0x1A) {
+        Some(end) => &bytes[..end],
+        None => bytes,
+    };
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes
+            .iter()
+            .map(|b| match b {
+                0..=127 => *b as char,
+                _ => CP437[*b as usize - 128],
+            })
+            .collect(),
+    }
+}
+
+/// Largest screen the art is drawn on, wider and taller than any art made to
+/// be looked at, so that a file moving the cursor far away cannot make it
+/// take gigabytes; what falls outside is dropped
+const MAX_WIDTH: usize = 1024;
+const MAX_HEIGHT: usize = 4096;
+
+/// Most cells the frames of an art file take together, some 2000 screens of
+/// 80x25, as every frame is a copy of the screen and a small file could
+/// otherwise draw far away and go home over and over
+const MAX_CELLS: usize = 4 * 1024 * 1024;
+
+/// The screen the art is drawn on, which grows to fit it
+#[derive(Default)]
+struct Screen {
+    rows: Vec<Vec<Cell>>,
+    x: usize,
+    y: usize,
+    saved: (usize, usize),
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    /// Whether anything was drawn since the last frame was taken
+    drawn: bool,
+    /// Cells of the frames taken so far
+    cells: usize,
+}
+
+impl Screen {
+    fn put(&mut self, ch: char, wrap: Option<usize>) {
+        if self.x >= MAX_WIDTH || self.y >= MAX_HEIGHT {
+            return;
+        }
+        if self.rows.len() <= self.y {
+            self.rows.resize(self.y + 1, Vec::new());
+        }
+        let row = &mut self.rows[self.y];
+        if row.len() <= self.x {
+            row.resize(self.x + 1, Cell::new(' '));
+        }
+        row[self.x] = Cell {
+            ch,
+            fg: self.fg,
+            bg: self.bg,
+        };
+        self.drawn = true;
+
+        self.x += 1;
+        if wrap.is_some_and(|width| self.x >= width) {
+            self.x = 0;
+            self.y += 1;
+        }
+    }
+
+    /// Adds what is on the screen to the frames, unless it is blank or was
+    /// taken already, as going home without drawing anything does not redraw
+    fn take_frame(&mut self, frames: &mut Vec<Canvas>) -> Result<(), String> {
+        let (width, height) = self.extent();
+        if self.drawn && height > 0 {
+            self.cells += width * height;
+            if self.cells > MAX_CELLS {
+                return Err(format!("the frames take more than {} cells", MAX_CELLS));
+            }
+            frames.push(self.to_canvas(width, height));
+        }
+        self.drawn = false;
+        Ok(())
+    }
+
+    /// The size of what was drawn, leaving out the blank cells past the last
+    /// drawn one of the rows and the blank rows at the bottom
+    fn extent(&self) -> (usize, usize) {
+        let drawn = |c: &Cell| c.ch != ' ' || c.bg.is_some();
+        let width = self
+            .rows
+            .iter()
+            .map(|row| row.iter().rposition(drawn).map_or(0, |x| x + 1))
+            .max()
+            .unwrap_or(0);
+        let height = self
+            .rows
+            .iter()
+            .rposition(|row| row.iter().any(drawn))
+            .map_or(0, |y| y + 1);
+        (width, height)
+    }
+
+    fn to_canvas(&self, width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for (y, row) in self.rows.iter().take(height).enumerate() {
+            for (x, cell) in row.iter().take(width).enumerate() {
+                canvas.set(x, y, *cell);
+            }
+        }
+        canvas
+    }
+}
+
+/// Plays the escapes of the text on a screen, taking a frame whenever it is
+/// cleared or the cursor goes back home, which is how ANSImations redraw.
+/// Escapes it does not know are ignored.
+pub fn parse(text: &str, wrap: Option<usize>) -> Result<Vec<Canvas>, String> {
+    let mut screen = Screen::default();
+    let mut frames = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => screen.x = 0,
+            '\n' => {
+                screen.x = 0;
+                screen.y = (screen.y + 1).min(MAX_HEIGHT);
+            }
+            '\t' => screen.x = ((screen.x / 8 + 1) * 8).min(MAX_WIDTH),
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut sequence = String::new();
+                let mut end = None;
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        end = Some(c);
+                        break;
+                    }
+                    sequence.push(c);
+                }
+                let params: Vec<usize> = sequence
+                    .trim_start_matches('?')
+                    .split(';')
+                    .map(|p| p.parse().unwrap_or(0))
+                    .collect();
+                let count = params[0].max(1);
+
+                match end {
+                    Some('m') => apply_sgr(&sequence, &mut screen),
+                    Some('A') => screen.y = screen.y.saturating_sub(count),
+                    Some('B') => screen.y = screen.y.saturating_add(count).min(MAX_HEIGHT),
+                    Some('C') => screen.x = screen.x.saturating_add(count).min(MAX_WIDTH),
+                    Some('D') => screen.x = screen.x.saturating_sub(count),
+                    Some('H' | 'f') => {
+                        let row = params[0].max(1) - 1;
+                        let column = params.get(1).copied().unwrap_or(1).max(1) - 1;
+                        if (row, column) == (0, 0) {
+                            screen.take_frame(&mut frames)?;
+                        }
+                        (screen.x, screen.y) = (column.min(MAX_WIDTH), row.min(MAX_HEIGHT));
+                    }
+                    Some('J') if params[0] == 2 => {
+                        screen.take_frame(&mut frames)?;
+                        screen.rows.clear();
+                        (screen.x, screen.y) = (0, 0);
+                    }
+                    Some('K') => {
+                        if let Some(row) = screen.rows.get_mut(screen.y) {
+                            row.truncate(screen.x);
+                        }
+                    }
+                    Some('s') => screen.saved = (screen.x, screen.y),
+                    Some('u') => (screen.x, screen.y) = screen.saved,
+                    _ => {}
+                }
+            }
+            c if c.is_control() => {}
+            c => screen.put(c, wrap),
+        }
+    }
+
+    screen.take_frame(&mut frames)?;
+    Ok(frames)
+}
+
+/// Applies the parameters of a Select Graphic Rendition escape, where bold
+/// makes the basic colors bright, as DOS art expects
+fn apply_sgr(sequence: &str, screen: &mut Screen) {
+    let codes: Vec<u8> = sequence
+        .split(';')
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+
+    let mut codes = codes.iter().copied();
+    while let Some(code) = codes.next() {
+        match code {
+            0 => {
+                screen.fg = None;
+                screen.bg = None;
+                screen.bold = false;
+            }
+            1 => {
+                screen.bold = true;
+                screen.fg = screen.fg.map(brighten);
+            }
+            22 => screen.bold = false,
+            30..=37 => screen.fg = Some(basic_color(code - 30, screen.bold)),
+            90..=97 => screen.fg = Some(basic_color(code - 90, true)),
+            40..=47 => screen.bg = Some(basic_color(code - 40, false)),
+            100..=107 => screen.bg = Some(basic_color(code - 100, true)),
+            39 => screen.fg = None,
+            49 => screen.bg = None,
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(Color::Indexed),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if code == 38 {
+                    screen.fg = color;
+                } else {
+                    screen.bg = color;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn basic_color(index: u8, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+fn brighten(color: Color) -> Color {
+    match color {
+        Color::Black => Color::DarkGray,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        Color::Gray => Color::White,
+        other => other,
+    }
+}
+
+/// The frames of an art file or a recording, played one after the other
+pub struct Animation {
+    /// Each frame with when it is shown, from the start
+    frames: Vec<(Duration, Canvas)>,
+    current: usize,
+    next_at: Instant,
+    /// One of `SPEEDS`
+    pub speed: f32,
+    /// The first and last frames of the part played over and over, A and B
+    pub loop_a: Option<usize>,
+    pub loop_b: Option<usize>,
+}
+
+impl Animation {
+    /// Frames shown at the pace of an ANSImation
+    pub fn new(frames: Vec<Canvas>) -> Animation {
+        Animation::timed(
+            frames
+                .into_iter()
+                .enumerate()
+                .map(|(i, frame)| (FRAME_INTERVAL * i as u32, frame))
+                .collect(),
+        )
+    }
+
+    /// Frames shown at the times they come with
+    pub fn timed(frames: Vec<(Duration, Canvas)>) -> Animation {
+        let mut animation = Animation {
+            frames,
+            current: 0,
+            next_at: Instant::now(),
+            speed: 1.0,
+            loop_a: None,
+            loop_b: None,
+        };
+        animation.next_at += animation.delay();
+        animation
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// How long the current frame stays at the speed played at, the last one
+    /// as long as an ANSImation frame
+    fn delay(&self) -> Duration {
+        let delay = match self.frames.get(self.current + 1) {
+            Some((next, _)) => next.saturating_sub(self.frames[self.current].0),
+            None => FRAME_INTERVAL,
+        };
+        delay.div_f32(self.speed)
+    }
+
+    /// Plays slower or faster by `step` of the `SPEEDS`, returning the new speed
+    pub fn change_speed(&mut self, step: isize) -> f32 {
+        let index = SPEEDS
+            .iter()
+            .position(|&s| s == self.speed)
+            .unwrap_or(2)
+            .saturating_add_signed(step)
+            .min(SPEEDS.len() - 1);
+        self.speed = SPEEDS[index];
+        self.next_at = Instant::now() + self.delay();
+        self.speed
+    }
+
+    /// Moves `step` frames forward or back, wrapping around like the playback,
+    /// within the loop when both of its ends are marked
+    pub fn step(&mut self, step: isize) {
+        let (first, last) = match (self.loop_a, self.loop_b) {
+            (Some(a), Some(b)) => (a as isize, b as isize),
+            _ => (0, self.frames.len() as isize - 1),
+        };
+        let offset = (self.current as isize - first + step).rem_euclid(last - first + 1);
+        self.current = (first + offset) as usize;
+        self.next_at = Instant::now() + self.delay();
+    }
+
+    /// Position of the frame shown last among the frames
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// When the current frame is shown, from the start
+    pub fn position(&self) -> Duration {
+        self.frames[self.current].0
+    }
+
+    /// How long the frames take to play once at their own pace
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map_or(Duration::ZERO, |(time, _)| *time) + FRAME_INTERVAL
+    }
+
+    /// Jumps to the frame shown `offset` seconds before or after the current
+    /// one, or at least to the frame next to it, stopping at the first and last
+    pub fn seek(&mut self, offset: f32) {
+        let target = Duration::from_secs_f32((self.position().as_secs_f32() + offset).max(0.0));
+        let frame = self
+            .frames
+            .iter()
+            .rposition(|(time, _)| *time <= target)
+            .unwrap_or(0);
+
+        self.current = if offset > 0.0 {
+            frame.max((self.current + 1).min(self.frames.len() - 1))
+        } else {
+            frame.min(self.current.saturating_sub(1))
+        };
+        self.next_at = Instant::now() + self.delay();
+    }
+
+    /// Marks the current frame as the start of the loop, then as its end, and
+    /// clears the loop the third time
+    pub fn mark_loop(&mut self) {
+        match (self.loop_a, self.loop_b) {
+            (None, _) => self.loop_a = Some(self.current),
+            (Some(a), None) => {
+                self.loop_a = Some(a.min(self.current));
+                self.loop_b = Some(a.max(self.current));
+            }
+            (Some(_), Some(_)) => {
+                self.loop_a = None;
+                self.loop_b = None;
+            }
+        }
+    }
+
+    /// The frame to show, moving on to the next one when it is time unless
+    /// the viewer is paused
+    pub fn frame(&mut self, paused: bool) -> &Canvas {
+        if !paused && self.frames.len() > 1 && Instant::now() >= self.next_at {
+            self.step(1);
+        }
+        &self.frames[self.current].1
+    }
+
+    /// The frame shown last, without moving on
+    pub fn current(&self) -> &Canvas {
+        &self.frames[self.current].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(canvas: &Canvas) -> String {
+        canvas.to_string()
+    }
+
+    #[test]
+    fn plain_text_is_a_single_frame() {
+        let frames = parse("ab\r\ncd\n", None).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(text(&frames[0]), "ab\ncd\n");
+    }
+
+    #[test]
+    fn wraps_at_the_width_of_the_screen() {
+        let frames = parse("abcde", Some(2)).unwrap();
+        assert_eq!(text(&frames[0]), "ab\ncd\ne \n");
+    }
+
+    #[test]
+    fn colors_the_cells() {
+        let frames = parse(
+            "\x1b[31ma\x1b[1;44mb\x1b[0mc\x1b[38;5;208;48;2;1;2;3md",
+            None,
+        )
+        .unwrap();
+        let row = frames[0].row(0);
+        assert_eq!((row[0].fg, row[0].bg), (Some(Color::Red), None));
+        assert_eq!(
+            (row[1].fg, row[1].bg),
+            (Some(Color::LightRed), Some(Color::Blue))
+        );
+        assert_eq!((row[2].fg, row[2].bg), (None, None));
+        assert_eq!(
+            (row[3].fg, row[3].bg),
+            (Some(Color::Indexed(208)), Some(Color::Rgb(1, 2, 3)))
+        );
+    }
+
+    #[test]
+    fn moves_the_cursor() {
+        let frames = parse("a\x1b[2Cb\x1b[2;2Hc\x1b[sd\x1b[1A\x1b[Ce\x1b[uf", None).unwrap();
+        assert_eq!(text(&frames[0]), "a  be\n cf  \n");
+    }
+
+    #[test]
+    fn clearing_or_going_home_starts_a_frame() {
+        let frames = parse("one\x1b[2Jtwo\x1b[Hthree\x1b[H", None).unwrap();
+        let frames: Vec<String> = frames.iter().map(text).collect();
+        assert_eq!(frames, vec!["one\n", "two\n", "three\n"]);
+    }
+
+    #[test]
+    fn far_cursor_moves_stay_on_the_screen() {
+        let frames = parse("\x1b[99999;99999Ha\x1b[99999Bb\x1b[99999Cc\t\td", None).unwrap();
+        assert!(frames.is_empty());
+
+        let frames = parse("a\x1b[99999Cb", None).unwrap();
+        assert_eq!(frames[0].width, 1);
+    }
+
+    #[test]
+    fn frames_are_cropped_to_what_was_drawn() {
+        let frames = parse("ab   \r\n     \r\n", None).unwrap();
+        assert_eq!(text(&frames[0]), "ab\n");
+        // spaces on a background are drawn
+        let frames = parse("a\x1b[41m \x1b[0m  ", None).unwrap();
+        assert_eq!(frames[0].width, 2);
+    }
+
+    #[test]
+    fn frames_far_out_over_and_over_are_refused() {
+        // a few kilobytes asking for a full screen of cells with every frame
+        let text = "\x1b[4096;1024Hx\x1b[H".repeat(500);
+        assert!(text.len() < 16 * 1024);
+        assert!(parse(&text, None).is_err());
+
+        // while a long animation of ordinary screens still loads
+        let text = format!("{}\x1b[H", "x".repeat(80 * 25)).repeat(1000);
+        assert_eq!(parse(&text, Some(80)).unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn decodes_code_page_437() {
+        assert_eq!(decode(&[b'a', 0xB0, 0xDB]), "a░█");
+        assert_eq!(decode("é".as_bytes()), "é");
+        // the SAUCE record after the end-of-file character is left out
+        assert_eq!(decode(b"art\x1aSAUCE00"), "art");
+    }
+
+    /// Frames a tenth of a second apart, each showing its number
+    fn numbered(count: usize) -> Animation {
+        Animation::new(
+            (0..count)
+                .map(|i| Canvas::from_text(&i.to_string()))
+                .collect(),
+        )
+    }
+
+    fn shown(animation: &Animation) -> String {
+        animation.current().to_string()
+    }
+
+    #[test]
+    fn speeds_stop_at_the_ends() {
+        let mut animation = numbered(2);
+        assert_eq!(animation.change_speed(-1), 0.5);
+        assert_eq!(animation.change_speed(-5), 0.25);
+        assert_eq!(animation.change_speed(10), 4.0);
+    }
+
+    #[test]
+    fn steps_wrap_around() {
+        let mut animation = numbered(3);
+        animation.step(-1);
+        assert_eq!(shown(&animation), "2\n");
+        animation.step(1);
+        assert_eq!(shown(&animation), "0\n");
+    }
+
+    #[test]
+    fn loops_between_the_marks() {
+        let mut animation = numbered(5);
+        animation.step(3);
+        animation.mark_loop();
+        animation.step(-2);
+        animation.mark_loop();
+        // marked the wrong way round, the loop still runs forward
+        assert_eq!((animation.loop_a, animation.loop_b), (Some(1), Some(3)));
+
+        animation.step(2);
+        assert_eq!(shown(&animation), "3\n");
+        animation.step(1);
+        assert_eq!(shown(&animation), "1\n");
+        animation.step(-1);
+        assert_eq!(shown(&animation), "3\n");
+
+        animation.mark_loop();
+        assert_eq!((animation.loop_a, animation.loop_b), (None, None));
+        animation.step(1);
+        assert_eq!(shown(&animation), "4\n");
+    }
+
+    #[test]
+    fn seeks_by_time() {
+        let mut animation = numbered(100);
+        assert_eq!(animation.duration(), Duration::from_secs(10));
+
+        animation.seek(5.0);
+        assert_eq!(animation.position(), Duration::from_secs(5));
+        animation.seek(-20.0);
+        assert_eq!(animation.current_index(), 0);
+        animation.seek(20.0);
+        assert_eq!(animation.current_index(), 99);
+    }
+
+    #[test]
+    fn seeking_always_moves() {
+        let mut animation = Animation::timed(vec![
+            (Duration::ZERO, Canvas::from_text("0")),
+            (Duration::from_secs(60), Canvas::from_text("1")),
+        ]);
+        animation.seek(5.0);
+        assert_eq!(animation.current_index(), 1);
+        animation.seek(-5.0);
+        assert_eq!(animation.current_index(), 0);
+    }
+}
@@ -0,0 +1,1423 @@
+use chrono::Local;
+use image::{ImageBuffer, Rgb};
+use nokhwa::{
+    pixel_format::{LumaFormat, RgbFormat},
+    utils::{CameraFormat, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType},
+    Camera, NokhwaError,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+use tui::{layout::Rect, widgets::ListState};
+
+use crate::audio::{Microphone, Reactive, DEFAULT_DEPTH};
+use crate::backdrop::{Backdrop, LEARNING_DELAY};
+use crate::bayer::BayerPattern;
+use crate::canvas::Canvas;
+use crate::config::{
+    load_cell_aspect, load_last_camera, load_recent, save_cell_aspect, save_last_camera,
+    save_recent, MAX_RECENT,
+};
+use crate::deinterlace::Deinterlace;
+use crate::dialog::Dialog;
+use crate::dither::Hysteresis;
+use crate::exposure::AutoExposure;
+use crate::faces::FaceDetector;
+use crate::gallery::Gallery;
+use crate::hook::Hook;
+use crate::keymap::Keymap;
+use crate::lens::Lens;
+use crate::locale;
+use crate::logo::Logo;
+use crate::motion::MotionDetector;
+use crate::mqtt::Publisher;
+use crate::overlay::{Corner, Grid, Markers};
+use crate::playlist::{Item, Playlist};
+use crate::presets::Presets;
+use crate::preview::Preview;
+use crate::privacy::{Mask, Privacy};
+use crate::prompter::{Prompter, DEFAULT_SPEED};
+use crate::qr::QrScanner;
+use crate::recorder::{RecordFormat, Recorder};
+use crate::replay::ReplayBuffer;
+use crate::retro::Palette;
+use crate::settings::{on_off, Charset, ColorMode, Setting, Settings};
+use crate::snapshot::{self, Burst};
+use crate::source::{clean_path, is_url, Source};
+use crate::stabilizer::Stabilizer;
+use crate::theme::Theme;
+use crate::timer::SelfTimer;
+use crate::toast::Toasts;
+use crate::viewport::Viewport;
+use crate::webhook::{json_string, Webhook};
+
+/// How often the viewer grabs a frame while idle
+const IDLE_TICK_RATE: Duration = Duration::from_millis(500);
+
+/// How long the view border stays highlighted after a capture
+const FLASH_DURATION: Duration = Duration::from_millis(250);
+
+/// How many times taller than wide terminal cells are, until calibrated
+const DEFAULT_CELL_ASPECT: f32 = 2.0;
+
+/// The range and step of the calibration
+const MIN_CELL_ASPECT: f32 = 1.0;
+const MAX_CELL_ASPECT: f32 = 3.0;
+const CELL_ASPECT_STEP: f32 = 0.05;
+
+/// The most pixels of the frame a glyph can cover
+const MAX_CELL_PIXELS: i32 = 64;
+
+/// Opens a camera and starts its stream, in the given format if it has it
+pub fn open_camera(
+    index: &CameraIndex,
+    format: Option<FrameFormat>,
+) -> Result<Camera, NokhwaError> {
+    let mut camera = match format {
+        // the format may not reach the highest frame rate the camera has, in
+        // which case its first mode is taken
+        Some(format) => {
+            let formats = [format];
+            Camera::new(
+                index.clone(),
+                RequestedFormat::with_formats(
+                    RequestedFormatType::AbsoluteHighestFrameRate,
+                    &formats,
+                ),
+            )
+            .or_else(|_| {
+                Camera::new(
+                    index.clone(),
+                    RequestedFormat::with_formats(RequestedFormatType::None, &formats),
+                )
+            })?
+        }
+        // cameras with only gray frames, such as raw Bayer sensors, have none of
+        // the color formats and need a request that includes gray
+        None => Camera::new(
+            index.clone(),
+            RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate),
+        )
+        .or_else(|_| {
+            Camera::new(
+                index.clone(),
+                RequestedFormat::new::<LumaFormat>(RequestedFormatType::AbsoluteHighestFrameRate),
+            )
+        })?,
+    };
+    camera.open_stream()?;
+
+    Ok(camera)
+}
+
+/// Opens a camera at a low resolution, which is all a small rendering needs and
+/// leaves room for several cameras on the same USB bus
+pub fn open_small_camera(index: &CameraIndex) -> Result<Camera, NokhwaError> {
+    let small = CameraFormat::new_from(320, 240, FrameFormat::MJPEG, 15);
+    let mut camera = match Camera::new(
+        index.clone(),
+        RequestedFormat::new::<RgbFormat>(RequestedFormatType::Closest(small)),
+    ) {
+        Ok(camera) => camera,
+        // a camera without anything close opens like it would in the viewer
+        Err(_) => return open_camera(index, None),
+    };
+    camera.open_stream()?;
+
+    Ok(camera)
+}
+
+/// Opens a camera on a background thread, since some drivers take seconds to respond
+pub fn open_camera_async(
+    index: &CameraIndex,
+    format: Option<FrameFormat>,
+) -> Receiver<Result<Camera, NokhwaError>> {
+    let index = index.clone();
+    in_background(move || open_camera(&index, format))
+}
+
+/// Opens a camera at a low resolution on a background thread
+pub fn open_small_camera_async(index: &CameraIndex) -> Receiver<Result<Camera, NokhwaError>> {
+    let index = index.clone();
+    in_background(move || open_small_camera(&index))
+}
+
+fn in_background(
+    open: impl FnOnce() -> Result<Camera, NokhwaError> + Send + 'static,
+) -> Receiver<Result<Camera, NokhwaError>> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        // the receiver is gone if the user cancelled, the camera is just dropped
+        let _ = sender.send(open());
+    });
+
+    receiver
+}
+
+/// A camera being opened in the background
+pub struct Opening {
+    /// Position of the camera in the camera list
+    pub index: usize,
+    receiver: Receiver<Result<Camera, NokhwaError>>,
+    pub started: Instant,
+}
+
+/// Everything that affects how a frame is turned into text
+#[derive(Clone, PartialEq)]
+pub struct RenderKey {
+    pub size: (u16, u16),
+    pub settings: Settings,
+    pub viewport: Viewport,
+    pub privacy: Privacy,
+    /// Settings of the left half, when the view is split
+    pub before: Option<Settings>,
+}
+
+pub struct StatefulList<T> {
+    pub state: ListState,
+    pub items: Vec<T>,
+    // mirrors the scroll offset tui keeps private inside `ListState`
+    offset: usize,
+}
+
+impl<T> StatefulList<T> {
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        StatefulList {
+            state: ListState::default(),
+            items,
+            offset: 0,
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.items.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    pub fn select_first(&mut self) {
+        self.state.select((!self.items.is_empty()).then_some(0));
+    }
+
+    pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.items.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Scrolls just enough to keep the selection within `height` visible rows,
+    /// the same way the `List` widget does when it is rendered
+    pub fn scroll_into_view(&mut self, height: usize) {
+        let selected = self.state.selected().unwrap_or(0);
+        if selected < self.offset {
+            self.offset = selected;
+        } else if height > 0 && selected >= self.offset + height {
+            self.offset = selected + 1 - height;
+        }
+    }
+
+    /// Moves the selection by `step` items, stopping at either end of the list,
+    /// which is how paging through a long list works
+    pub fn jump(&mut self, step: i32) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let current = self.state.selected().unwrap_or(0) as i32;
+        let i = (current + step).clamp(0, self.items.len() as i32 - 1);
+        self.state.select(Some(i as usize));
+    }
+
+    /// Returns the page of `height` rows the selection is on and how many pages
+    /// there are, both counted from 1
+    pub fn page(&self, height: usize) -> (usize, usize) {
+        let height = height.max(1);
+        let selected = self.state.selected().unwrap_or(0);
+        (
+            selected / height + 1,
+            self.items.len().max(1).div_ceil(height),
+        )
+    }
+
+    /// Returns the index of the item drawn on the given visible row
+    pub fn item_at(&self, row: usize) -> Option<usize> {
+        let index = self.offset + row;
+        if index < self.items.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct FpsCounter {
+    last_update: Instant,
+    frames: u32,
+    pub fps: f64,
+}
+
+impl FpsCounter {
+    pub fn new() -> FpsCounter {
+        FpsCounter {
+            last_update: Instant::now(),
+            frames: 0,
+            fps: 0.0,
+        }
+    }
+
+    /// Counts a rendered frame, refreshing the measured rate about once a second
+    pub fn tick(&mut self) {
+        self.frames += 1;
+
+        let elapsed = self.last_update.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f64 / elapsed.as_secs_f64();
+            self.frames = 0;
+            self.last_update = Instant::now();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = FpsCounter::new();
+    }
+}
+
+pub struct App {
+    pub cameras: Vec<(String, CameraIndex)>,
+    /// Indices of the cameras matching the filter, in display order
+    pub menu: StatefulList<usize>,
+    pub filter: String,
+    /// Whether key presses are currently typed into the filter
+    pub filtering: bool,
+    /// What the viewer shows, `None` while in the menu
+    pub source: Option<Source>,
+    /// Path typed or pasted in the open-file prompt, `None` when it is closed
+    pub open_prompt: Option<String>,
+    /// Files opened lately, the latest first, reopened from the menu by their digit
+    pub recent: Vec<String>,
+    pub opening: Option<Opening>,
+    pub preview: Option<Preview>,
+    pub paused: bool,
+    /// Whether the viewer pauses by itself when nothing moves
+    pub auto_pause: bool,
+    pub auto_pause_after: Duration,
+    /// Whether the current pause comes from the lack of motion, and ends with motion
+    pub auto_paused: bool,
+    pub last_frame: Option<ImageBuffer<Rgb<u8>, Vec<u8>>>,
+    pub replay: ReplayBuffer,
+    /// The text of the paused frame, along with what it was rendered for
+    pub render_cache: Option<(RenderKey, Canvas)>,
+    pub fps: FpsCounter,
+    pub motion: MotionDetector,
+    /// How long the image has to stay still before the viewer idles, `None` to never idle
+    pub idle_after: Option<Duration>,
+    pub capture_size: Option<(u32, u32)>,
+    pub show_help: bool,
+    /// Lines of the help overlay scrolled past
+    pub help_scroll: u16,
+    /// Whether the Info panel is shown under the camera list
+    pub show_info: bool,
+    pub dialog: Option<Dialog>,
+    pub toasts: Toasts,
+    pub settings: Settings,
+    pub settings_panel: StatefulList<Setting>,
+    pub presets: Presets,
+    /// The other side of the A/B comparison, once it has been started
+    pub alternate: Option<Settings>,
+    /// Whether `settings` currently holds the B side
+    pub showing_b: bool,
+    pub show_settings: bool,
+    /// Ramps the Charset setting cycles through, the built-in ones followed
+    /// by those of the config
+    pub charsets: Vec<Charset>,
+    /// Ramp being typed in the charset editor, `None` when it is closed
+    pub charset_editor: Option<String>,
+    /// Whether the vim-style modal key scheme is used
+    pub vim_keys: bool,
+    /// Keys of the viewer's actions, the vim-style ones included
+    pub keymap: Keymap,
+    /// Text typed after `:` in the vim-style key scheme, `None` when not typing
+    pub command: Option<String>,
+    pub theme: Theme,
+    /// Where snapshots and recordings are saved
+    pub output_dir: PathBuf,
+    /// The snapshot browser, shown instead of the camera list while open
+    pub gallery: Option<Gallery>,
+    pub self_timer: SelfTimer,
+    /// The burst of snapshots being taken, if any
+    pub burst: Option<Burst>,
+    /// How many frames a burst saves and how far apart
+    pub burst_count: u32,
+    pub burst_interval: Duration,
+    /// Whether captures ring the terminal bell
+    pub bell: bool,
+    /// Whether captures briefly flash the view border
+    pub flash: bool,
+    pub flash_until: Option<Instant>,
+    /// The recording in progress, if any
+    pub recorder: Option<Recorder>,
+    /// What recordings are saved as
+    pub record_format: RecordFormat,
+    /// The last frame drawn in the viewer, without the overlays
+    pub last_canvas: Option<Canvas>,
+    pub show_histogram: bool,
+    /// Luma histogram of the last rendered frame, only kept up to date while shown
+    pub histogram: [u64; 256],
+    pub grid: Grid,
+    pub markers: Markers,
+    /// Looks for faces to draw boxes around, `None` when turned off
+    pub faces: Option<FaceDetector>,
+    /// Looks for QR codes, `None` when turned off
+    pub qr: Option<QrScanner>,
+    /// Path of the SeetaFace model the face detector loads
+    pub face_model: Option<PathBuf>,
+    /// Art drawn behind the subject, `None` when turned off
+    pub backdrop: Option<Backdrop>,
+    /// Path of the ASCII or ANSI art the backdrop loads
+    pub backdrop_art: Option<PathBuf>,
+    /// Frame format requested from cameras, set from the config, or the best one when `None`
+    pub camera_format: Option<FrameFormat>,
+    /// Camera whose gray frames are demosaiced with the pattern, set from the config
+    pub bayer: Option<(String, BayerPattern)>,
+    /// Listens to the microphone for the level meter, `None` when turned off
+    pub microphone: Option<Microphone>,
+    /// Setting driven by the microphone level
+    pub reactive: Reactive,
+    /// How far the loudest sound moves the driven setting
+    pub reactive_depth: f32,
+    /// Corrects the distortion of wide-angle lenses, set from the config
+    pub lens: Option<Lens>,
+    /// Deinterlacing of the open source, remembered per source
+    pub deinterlace: Deinterlace,
+    /// Shows the image without the change being made on the left half of the view
+    pub split: bool,
+    /// Gain applied to frames when the exposure setting is auto
+    pub exposure: AutoExposure,
+    /// How many pixels of the frame go across one glyph, the image then being
+    /// drawn at that size in the middle of the view; `None` to fill the view
+    pub cell_pixels: Option<u32>,
+    /// How many times taller than wide a cell of the terminal is
+    pub cell_aspect: f32,
+    /// The cell aspect being tried on the calibration screen, `None` when it is closed
+    pub calibration: Option<f32>,
+    /// Whether the terminal can draw glyphs beyond ASCII, probed from the
+    /// locale unless the config says
+    pub unicode: bool,
+    /// Last glyph levels, which keep cells on a boundary from flickering
+    pub hysteresis: Hysteresis,
+    /// Cancels out camera shake, `None` when turned off
+    pub stabilizer: Option<Stabilizer>,
+    /// Text scrolling across the bottom of the view, `None` when hidden
+    pub prompter: Option<Prompter>,
+    /// Path of the text file the prompter scrolls
+    pub prompter_file: Option<PathBuf>,
+    /// Scrolling speed of the prompter, in cells per second
+    pub prompter_speed: f32,
+    /// Publishes motion events to an MQTT broker, set from the config
+    pub mqtt: Option<Publisher>,
+    /// Posts motion events, set from the config
+    pub motion_webhook: Option<Webhook>,
+    /// Posts the snapshots taken, set from the config
+    pub snapshot_webhook: Option<Webhook>,
+    /// Runs on every snapshot saved, set from the config
+    pub snapshot_hook: Option<Hook>,
+    /// Runs on every recording finished, set from the config
+    pub recording_hook: Option<Hook>,
+    /// Every camera at once, shown instead of the camera list while open
+    pub monitor: Option<Vec<Preview>>,
+    /// Sources shown one after the other, set from the config
+    pub playlist: Option<Playlist>,
+    /// Image drawn over every frame, set from the config
+    pub logo: Option<Logo>,
+    /// Where the clock is drawn over the image, `None` when hidden
+    pub clock: Option<Corner>,
+    /// Where the clock goes when it is turned on
+    pub clock_corner: Corner,
+    /// Hides the borders, title and status bar around the image
+    pub fullscreen: bool,
+    pub viewport: Viewport,
+    pub privacy: Privacy,
+    /// Cells the mouse was pressed on and dragged to while selecting a privacy region
+    pub mask_drag: Option<((u16, u16), (u16, u16))>,
+    /// Last cell the mouse was dragged from while panning
+    pub drag_origin: Option<(u16, u16)>,
+    /// Where the camera list was last drawn, used to resolve mouse clicks
+    pub menu_area: Rect,
+    /// Where the image was last drawn, used to resolve mouse drags
+    pub view_area: Rect,
+}
+
+impl App {
+    pub fn new() -> App {
+        // images and recordings need no camera, so failing to list them is
+        // only worth a toast
+        let (cameras, query_error) = match nokhwa::query(nokhwa::utils::ApiBackend::Auto) {
+            Ok(cs) => (cs, None),
+            Err(e) => (
+                Vec::new(),
+                Some(format!("Could not list the cameras: {}", e)),
+            ),
+        };
+
+        let cameras: Vec<(String, CameraIndex)> = cameras
+            .iter()
+            .map(|c| (c.human_name(), c.index().clone()))
+            .collect();
+
+        let mut app = App {
+            menu: StatefulList::with_items((0..cameras.len()).collect()),
+            cameras,
+            filter: String::new(),
+            filtering: false,
+            source: None,
+            open_prompt: None,
+            recent: load_recent(),
+            opening: None,
+            preview: None,
+            paused: false,
+            auto_pause: false,
+            auto_pause_after: Duration::from_secs(10),
+            auto_paused: false,
+            last_frame: None,
+            replay: ReplayBuffer::default(),
+            render_cache: None,
+            fps: FpsCounter::new(),
+            motion: MotionDetector::default(),
+            idle_after: None,
+            capture_size: None,
+            show_help: false,
+            help_scroll: 0,
+            show_info: true,
+            dialog: None,
+            toasts: Toasts::default(),
+            settings: Settings::default(),
+            settings_panel: StatefulList::with_items(Setting::ALL.to_vec()),
+            presets: Presets::load(),
+            alternate: None,
+            showing_b: false,
+            show_settings: false,
+            charsets: Charset::builtin(),
+            charset_editor: None,
+            vim_keys: false,
+            keymap: Keymap::default(),
+            command: None,
+            theme: Theme::default(),
+            output_dir: PathBuf::from("."),
+            gallery: None,
+            self_timer: SelfTimer::default(),
+            burst: None,
+            burst_count: 5,
+            burst_interval: Duration::from_millis(200),
+            bell: false,
+            flash: true,
+            flash_until: None,
+            recorder: None,
+            record_format: RecordFormat::Text,
+            last_canvas: None,
+            show_histogram: false,
+            histogram: [0; 256],
+            grid: Grid::Off,
+            markers: Markers::Off,
+            faces: None,
+            face_model: None,
+            camera_format: None,
+            bayer: None,
+            microphone: None,
+            reactive: Reactive::Off,
+            reactive_depth: DEFAULT_DEPTH,
+            lens: None,
+            deinterlace: Deinterlace::Off,
+            split: false,
+            exposure: AutoExposure::default(),
+            cell_pixels: None,
+            cell_aspect: load_cell_aspect().unwrap_or(DEFAULT_CELL_ASPECT),
+            calibration: None,
+            unicode: locale::unicode_supported(),
+            hysteresis: Hysteresis::default(),
+            stabilizer: None,
+            prompter: None,
+            prompter_file: None,
+            prompter_speed: DEFAULT_SPEED,
+            mqtt: None,
+            motion_webhook: None,
+            snapshot_webhook: None,
+            snapshot_hook: None,
+            recording_hook: None,
+            monitor: None,
+            playlist: None,
+            logo: None,
+            backdrop: None,
+            backdrop_art: None,
+            qr: None,
+            clock: None,
+            clock_corner: Corner::TopRight,
+            fullscreen: false,
+            viewport: Viewport::default(),
+            privacy: Privacy::default(),
+            mask_drag: None,
+            drag_origin: None,
+            menu_area: Rect::default(),
+            view_area: Rect::default(),
+        };
+
+        app.menu.select_first();
+        app.settings_panel.select_first();
+        if let Some(error) = query_error {
+            app.toasts.push(error);
+        }
+        app
+    }
+
+    /// Returns the position in `cameras` of the highlighted menu entry
+    pub fn selected_camera(&self) -> Option<usize> {
+        self.menu
+            .selected()
+            .and_then(|i| self.menu.items.get(i).copied())
+    }
+
+    /// Rebuilds the menu from the cameras whose name contains the filter
+    pub fn apply_filter(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.menu.items = self
+            .cameras
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, _))| name.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect();
+
+        if self.menu.items.is_empty() {
+            self.menu.state.select(None);
+        } else {
+            self.menu.select_first();
+        }
+    }
+
+    pub fn open_selected_camera(&mut self) {
+        let index = match self.selected_camera() {
+            Some(index) => index,
+            None => return,
+        };
+        self.open_camera_at(index);
+    }
+
+    fn open_camera_at(&mut self, index: usize) {
+        // the preview holds on to the device, release it first
+        self.preview = None;
+
+        self.opening = Some(Opening {
+            index,
+            receiver: open_camera_async(&self.cameras[index].1, self.camera_format),
+            started: Instant::now(),
+        });
+    }
+
+    /// Picks up the camera opened in the background once it is ready
+    pub fn poll_opening(&mut self) {
+        let result = match self.opening.as_ref() {
+            Some(opening) => match opening.receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => Err(NokhwaError::GeneralError(String::from(
+                    "the camera thread stopped unexpectedly",
+                ))),
+            },
+            None => return,
+        };
+        let index = match self.opening.take() {
+            Some(opening) => opening.index,
+            None => return,
+        };
+
+        match result {
+            Ok(camera) => {
+                let name = &self.cameras[index].0;
+                self.deinterlace = Deinterlace::load(name);
+                let bayer = self
+                    .bayer
+                    .as_ref()
+                    .filter(|(camera, _)| camera == name)
+                    .map(|(_, pattern)| *pattern);
+                self.source = Some(Source::Camera { camera, bayer });
+                self.fps.reset();
+                save_last_camera(&self.cameras[index].0);
+            }
+            Err(e) => {
+                let message = format!("{}: {}", self.cameras[index].0, e);
+                self.dialog = Some(Dialog::camera_error(message));
+            }
+        };
+    }
+
+    /// Reports motion that starts after a quiet spell, and any failure to do so
+    pub fn poll_motion_events(&mut self) {
+        if let Some(score) = self.motion.take_event() {
+            let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+            if let Some(mqtt) = self.mqtt.as_ref() {
+                mqtt.publish(format!(
+                    "{{\"timestamp\":\"{}\",\"score\":{:.1}}}",
+                    timestamp, score
+                ));
+            }
+            if let Some(webhook) = self.motion_webhook.as_ref() {
+                webhook.post(format!(
+                    "{{\"event\":\"motion\",\"timestamp\":\"{}\",\"score\":{:.1}}}",
+                    timestamp, score
+                ));
+            }
+        }
+
+        if let Some(e) = self.mqtt.as_ref().and_then(|m| m.errors.try_recv().ok()) {
+            self.toasts
+                .push(format!("Could not publish to MQTT: {}", e));
+        }
+        for webhook in [&self.motion_webhook, &self.snapshot_webhook] {
+            if let Some(e) = webhook.as_ref().and_then(|w| w.errors.try_recv().ok()) {
+                self.toasts
+                    .push(format!("Could not post to the webhook: {}", e));
+            }
+        }
+    }
+
+    /// Shows why the commands run on saved files failed
+    pub fn poll_hook_errors(&mut self) {
+        for hook in [&self.snapshot_hook, &self.recording_hook] {
+            if let Some(e) = hook.as_ref().and_then(|h| h.errors.try_recv().ok()) {
+                self.toasts.push(format!("The hook failed: {}", e));
+            }
+        }
+    }
+
+    /// Opens every camera at once, in place of the menu preview
+    pub fn open_monitor(&mut self) {
+        // the preview holds on to one of the devices
+        self.preview = None;
+        self.monitor = Some(
+            self.cameras
+                .iter()
+                .enumerate()
+                .map(|(i, (_, index))| Preview::open(i, index))
+                .collect(),
+        );
+    }
+
+    /// Shows the current item of the playlist in place of whatever is open
+    pub fn play_current(&mut self) {
+        let item = match self.playlist.as_ref() {
+            Some(playlist) => playlist.current().clone(),
+            None => return,
+        };
+
+        self.close_camera();
+        match item {
+            Item::Camera(name) => match self.cameras.iter().position(|(n, _)| *n == name) {
+                Some(index) => self.open_camera_at(index),
+                None => self
+                    .toasts
+                    .push(format!("There is no camera named {}", name)),
+            },
+            Item::File(path) => self.open_path(&path.to_string_lossy()),
+        }
+    }
+
+    /// Moves on to the next item of the playlist once the current one has been
+    /// shown for long enough, unless the viewer is paused
+    pub fn poll_playlist(&mut self) {
+        let due = self.playlist.as_ref().is_some_and(Playlist::due);
+        if !due || self.paused || self.opening.is_some() {
+            return;
+        }
+
+        if let Some(playlist) = self.playlist.as_mut() {
+            playlist.advance();
+        }
+        self.play_current();
+    }
+
+    /// Opens the camera used last time, staying on the menu if it is gone
+    pub fn connect_to_last(&mut self) {
+        let name = match load_last_camera() {
+            Some(name) => name,
+            None => return,
+        };
+
+        if let Some(position) = self
+            .menu
+            .items
+            .iter()
+            .position(|i| self.cameras[*i].0 == name)
+        {
+            self.menu.state.select(Some(position));
+            self.open_selected_camera();
+        }
+    }
+
+    /// Keeps the menu preview pointed at the highlighted camera and refreshes it
+    pub fn update_preview(&mut self, width: u16, height: u16) {
+        let index = match self.selected_camera() {
+            Some(index) => index,
+            None => {
+                self.preview = None;
+                return;
+            }
+        };
+
+        if self.preview.as_ref().map(|p| p.index) != Some(index) {
+            // release the previous device before opening the next one
+            self.preview = None;
+            self.preview = Some(Preview::open(index, &self.cameras[index].1));
+        }
+
+        if let Some(preview) = self.preview.as_mut() {
+            preview.refresh(width, height, &self.settings);
+        }
+    }
+
+    /// Switches to the look saved in a slot, keeping the frame rate
+    pub fn apply_preset(&mut self, slot: usize) {
+        match self.presets.get(slot) {
+            Some(preset) => {
+                let target_fps = self.settings.target_fps;
+                self.settings = preset.clone();
+                self.settings.target_fps = target_fps;
+                self.toasts.push(format!("Preset {}", slot));
+            }
+            None => self.toasts.push(format!("Preset {} is empty", slot)),
+        }
+    }
+
+    /// Flips between the A and B looks. The first flip starts B as a copy of A,
+    /// and both keep the changes made while they are shown.
+    pub fn flip_ab(&mut self) {
+        let target_fps = self.settings.target_fps;
+        let other = self
+            .alternate
+            .take()
+            .unwrap_or_else(|| self.settings.clone());
+
+        self.alternate = Some(std::mem::replace(&mut self.settings, other));
+        self.settings.target_fps = target_fps;
+        self.showing_b = !self.showing_b;
+    }
+
+    pub fn save_preset(&mut self, slot: usize) {
+        match self.presets.save(slot, &self.settings) {
+            Ok(()) => self.toasts.push(format!("Saved preset {}", slot)),
+            Err(e) => self
+                .toasts
+                .push(format!("Could not save the preset: {}", e)),
+        }
+    }
+
+    /// Whether nothing moved for long enough to save power. Only a live
+    /// viewer idles; the menu and a paused frame cost little anyway.
+    pub fn is_idle(&self) -> bool {
+        match self.idle_after {
+            Some(after) => {
+                self.source.is_some() && !self.paused && self.motion.still_for() >= after
+            }
+            None => false,
+        }
+    }
+
+    /// How long to wait between two frames
+    pub fn tick_rate(&self) -> Duration {
+        // watching for motion while paused needs no more than the idle rate
+        if self.is_idle() || self.auto_paused {
+            IDLE_TICK_RATE.max(self.settings.tick_rate())
+        } else {
+            self.settings.tick_rate()
+        }
+    }
+
+    /// Turns face detection on or off, loading the model when turned on
+    pub fn toggle_faces(&mut self) {
+        // a paused frame has to be rendered again to look for faces in it
+        self.render_cache = None;
+
+        if self.faces.take().is_some() {
+            return;
+        }
+
+        let model = match self.face_model.as_ref() {
+            Some(model) => model,
+            None => {
+                self.toasts
+                    .push("Set faces.model in the config to a SeetaFace model file");
+                return;
+            }
+        };
+
+        match FaceDetector::load(model) {
+            Ok(detector) => self.faces = Some(detector),
+            Err(e) => self
+                .toasts
+                .push(format!("Could not load the face model: {}", e)),
+        }
+    }
+
+    /// Switches to the next deinterlacing mode and remembers it for the open source
+    pub fn cycle_deinterlace(&mut self) {
+        let name = match self.source.as_ref() {
+            Some(source) => source.name(),
+            None => return,
+        };
+
+        self.deinterlace = self.deinterlace.next();
+        match self.deinterlace.save(&name) {
+            Ok(()) => self
+                .toasts
+                .push(format!("Deinterlacing {}", self.deinterlace.name())),
+            Err(e) => self
+                .toasts
+                .push(format!("Could not remember the deinterlacing: {}", e)),
+        }
+    }
+
+    /// Switches to the next setting the microphone drives, opening the microphone if needed
+    pub fn cycle_reactive(&mut self) {
+        self.reactive = self.reactive.next();
+        if self.reactive != Reactive::Off && self.microphone.is_none() {
+            self.toggle_microphone();
+        }
+        self.toasts
+            .push(format!("Audio-reactive {}", self.reactive.name()));
+    }
+
+    pub fn cycle_retro(&mut self) {
+        self.settings.retro = Palette::next(self.settings.retro);
+        match self.settings.retro {
+            Some(_) if !self.unicode => self
+                .toasts
+                .push("Retro needs a terminal that can draw block characters"),
+            retro => self
+                .toasts
+                .push(format!("Retro {}", retro.map_or("off", Palette::name))),
+        }
+    }
+
+    pub fn toggle_braille(&mut self) {
+        self.settings.braille = !self.settings.braille;
+        match self.settings.braille {
+            true if !self.unicode => self
+                .toasts
+                .push("Braille needs a terminal that can draw Unicode"),
+            braille => self.toasts.push(format!("Braille {}", on_off(braille))),
+        }
+    }
+
+    pub fn cycle_charset(&mut self, step: i32) {
+        self.settings.charset = self.settings.charset.cycle(&self.charsets, step);
+    }
+
+    /// Switches to the next character ramp, with braille after the last one
+    pub fn next_ramp(&mut self) {
+        let last = self.charsets.last() == Some(&self.settings.charset);
+        if self.settings.braille {
+            self.settings.braille = false;
+            self.settings.charset = self.charsets[0].clone();
+        } else if last {
+            self.settings.braille = true;
+        } else {
+            self.cycle_charset(1);
+        }
+
+        match self.settings.braille {
+            true if !self.unicode => self
+                .toasts
+                .push("Braille needs a terminal that can draw Unicode"),
+            true => self.toasts.push("Charset braille"),
+            false => self
+                .toasts
+                .push(format!("Charset {}", self.settings.charset.name)),
+        }
+    }
+
+    pub fn toggle_edges(&mut self) {
+        self.settings.edges = !self.settings.edges;
+        self.toasts
+            .push(format!("Edges {}", on_off(self.settings.edges)));
+    }
+
+    /// Plays an animation or a recording slower or faster
+    pub fn change_speed(&mut self, step: isize) {
+        match self
+            .source
+            .as_mut()
+            .and_then(|source| source.animation_mut())
+        {
+            Some(animation) => {
+                let speed = animation.change_speed(step);
+                self.toasts.push(format!("Speed {}x", speed));
+            }
+            None => self
+                .toasts
+                .push("Only animations and recordings have a speed"),
+        }
+    }
+
+    /// Marks where the loop of an animation or a recording starts, then where
+    /// it ends, then clears it
+    pub fn mark_loop(&mut self) {
+        let animation = match self
+            .source
+            .as_mut()
+            .and_then(|source| source.animation_mut())
+        {
+            Some(animation) => animation,
+            None => {
+                self.toasts.push("Only animations and recordings can loop");
+                return;
+            }
+        };
+
+        animation.mark_loop();
+        match (animation.loop_a, animation.loop_b) {
+            (Some(a), None) => self.toasts.push(format!("Loop from frame {}", a + 1)),
+            (Some(a), Some(b)) => self
+                .toasts
+                .push(format!("Loop frames {} to {}", a + 1, b + 1)),
+            _ => self.toasts.push("Loop cleared"),
+        }
+    }
+
+    /// Switches between plain glyphs and glyphs in 256 colors or true color
+    pub fn cycle_color(&mut self) {
+        self.settings.color_mode = match self.settings.color_mode {
+            ColorMode::Mono => ColorMode::Ansi256,
+            ColorMode::Ansi256 => ColorMode::Truecolor,
+            ColorMode::Truecolor | ColorMode::HalfBlock => ColorMode::Mono,
+        };
+        self.toasts
+            .push(format!("Color {}", self.settings.color_mode.name()));
+    }
+
+    /// Switches to ordered dithering, or to its next matrix size
+    pub fn cycle_ordered(&mut self) {
+        self.settings.algorithm = self.settings.algorithm.next_ordered();
+        self.toasts
+            .push(format!("Dithering {}", self.settings.algorithm.name()));
+    }
+
+    /// Shows the square test pattern, starting from the current cell aspect
+    pub fn open_calibration(&mut self) {
+        // the preview would draw over the pattern
+        self.preview = None;
+        self.calibration = Some(self.cell_aspect);
+    }
+
+    /// Makes the cells of the test pattern taller or shorter
+    pub fn adjust_calibration(&mut self, step: i32) {
+        if let Some(aspect) = self.calibration.as_mut() {
+            let steps = (*aspect / CELL_ASPECT_STEP).round() as i32 + step;
+            *aspect = (steps as f32 * CELL_ASPECT_STEP).clamp(MIN_CELL_ASPECT, MAX_CELL_ASPECT);
+        }
+    }
+
+    /// Keeps the cell aspect of the test pattern, for this time and the next ones
+    pub fn finish_calibration(&mut self) {
+        let aspect = match self.calibration.take() {
+            Some(aspect) => aspect,
+            None => return,
+        };
+
+        self.cell_aspect = aspect;
+        match save_cell_aspect(aspect) {
+            Ok(()) => self
+                .toasts
+                .push(format!("Cells are {:.2} times taller than wide", aspect)),
+            Err(e) => self
+                .toasts
+                .push(format!("Could not save the cell aspect: {}", e)),
+        }
+    }
+
+    /// Makes the glyphs cover more or fewer pixels of the frame, going back
+    /// to filling the view below one pixel
+    pub fn adjust_cell_pixels(&mut self, step: i32) {
+        let pixels = self.cell_pixels.unwrap_or(0) as i32 + step;
+        self.cell_pixels = (pixels > 0).then_some(pixels.min(MAX_CELL_PIXELS) as u32);
+        match self.cell_pixels {
+            Some(pixels) => self.toasts.push(format!("{} pixels per glyph", pixels)),
+            None => self.toasts.push("Glyphs fill the view"),
+        }
+    }
+
+    /// The settings a frame is rendered with, which the microphone may be driving
+    pub fn render_settings(&self) -> Settings {
+        let mut settings = self.settings.clone();
+        if let Some(microphone) = self.microphone.as_ref() {
+            self.reactive
+                .apply(&mut settings, microphone.loudness(), self.reactive_depth);
+        }
+        if !self.unicode {
+            settings.fall_back_to_ascii();
+        }
+        settings
+    }
+
+    /// The settings the left half of a split view is rendered with: those
+    /// without the setting highlighted in the panel, or without any
+    /// adjustment when the panel is hidden
+    pub fn before_settings(&self, settings: &Settings) -> Settings {
+        let mut before = settings.clone();
+        let highlighted = self
+            .settings_panel
+            .selected()
+            .map(|i| self.settings_panel.items[i]);
+        match highlighted {
+            Some(setting) if self.show_settings => before.restore(setting),
+            _ => before.reset_adjustments(),
+        }
+        before
+    }
+
+    /// Shows or hides the microphone level meter, opening the microphone when shown
+    pub fn toggle_microphone(&mut self) {
+        if self.microphone.take().is_some() {
+            return;
+        }
+
+        match Microphone::open() {
+            Ok(microphone) => {
+                self.toasts
+                    .push(format!("Listening to {}", microphone.name));
+                self.microphone = Some(microphone);
+            }
+            Err(e) => self
+                .toasts
+                .push(format!("Could not open the microphone: {}", e)),
+        }
+    }
+
+    /// Starts scrolling the prompter text from the beginning, or hides it
+    pub fn toggle_prompter(&mut self) {
+        if self.prompter.take().is_some() {
+            return;
+        }
+
+        let file = match self.prompter_file.as_ref() {
+            Some(file) => file,
+            None => {
+                self.toasts
+                    .push("Set prompter.file in the config to a text file");
+                return;
+            }
+        };
+
+        match Prompter::load(file, self.prompter_speed) {
+            Ok(prompter) => self.prompter = Some(prompter),
+            Err(e) => self
+                .toasts
+                .push(format!("Could not load the prompter text: {}", e)),
+        }
+    }
+
+    /// Turns the virtual background on or off, loading the art when turned on
+    pub fn toggle_backdrop(&mut self) {
+        self.render_cache = None;
+
+        if self.backdrop.take().is_some() {
+            return;
+        }
+
+        let art = match self.backdrop_art.as_ref() {
+            Some(art) => art,
+            None => {
+                self.toasts
+                    .push("Set background.art in the config to an ASCII or ANSI art file");
+                return;
+            }
+        };
+
+        match Backdrop::load(art) {
+            Ok(backdrop) => {
+                self.backdrop = Some(backdrop);
+                self.toasts.push(format!(
+                    "Step out of the frame, the background is learned in {}s",
+                    LEARNING_DELAY.as_secs()
+                ));
+            }
+            Err(e) => self
+                .toasts
+                .push(format!("Could not load the background art: {}", e)),
+        }
+    }
+
+    /// Maps a cell of the view to the frame, as fractions of the frame size
+    pub fn view_to_frame(&self, column: u16, row: u16) -> (f32, f32) {
+        let view = self.view_area;
+        let x = (column.saturating_sub(view.x) as f32 + 0.5) / view.width.max(1) as f32;
+        let y = (row.saturating_sub(view.y) as f32 + 0.5) / view.height.max(1) as f32;
+        let x = if self.settings.mirror { 1.0 - x } else { x };
+
+        self.viewport.to_frame(x, y)
+    }
+
+    /// Turns the selection dragged with the mouse into a privacy region
+    pub fn finish_mask(&mut self) {
+        let (start, end) = match self.mask_drag.take() {
+            Some(drag) => drag,
+            None => return,
+        };
+
+        let mask = Mask::from_corners(
+            self.view_to_frame(start.0, start.1),
+            self.view_to_frame(end.0, end.1),
+        );
+        if mask.width > 0.0 && mask.height > 0.0 {
+            self.privacy.masks.push(mask);
+        }
+    }
+
+    /// Pans the viewer in the direction the image is seen, which is the
+    /// opposite one horizontally when it is mirrored
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let dx = if self.settings.mirror { -dx } else { dx };
+        self.viewport.pan(dx, dy);
+    }
+
+    /// Puts the image adjustments and zoom back to how they started
+    pub fn reset_to_defaults(&mut self) {
+        self.settings.reset_adjustments();
+        self.viewport = Viewport::default();
+        self.drag_origin = None;
+    }
+
+    pub fn open_gallery(&mut self) {
+        match Gallery::open(&self.output_dir) {
+            Ok(gallery) => {
+                // the camera list is hidden, so its preview can let go of the device
+                self.preview = None;
+                self.gallery = Some(gallery);
+            }
+            Err(e) => self
+                .toasts
+                .push(format!("Could not list the snapshots: {}", e)),
+        }
+    }
+
+    /// Saves the last drawn frame and reports where it went
+    pub fn snapshot(&mut self) {
+        let canvas = match self.last_canvas.as_ref() {
+            Some(canvas) => canvas,
+            None => {
+                self.toasts.push("There is no frame to save yet");
+                return;
+            }
+        };
+
+        match snapshot::save(canvas, &self.output_dir) {
+            Ok(path) => {
+                self.announce_snapshot(&path, canvas);
+                self.toasts.push(format!("Saved {}", path.display()));
+                self.capture_feedback();
+            }
+            Err(e) => self
+                .toasts
+                .push(format!("Could not save the snapshot: {}", e)),
+        }
+    }
+
+    /// Posts a saved snapshot to the webhook and runs the hook on it
+    fn announce_snapshot(&self, path: &Path, canvas: &Canvas) {
+        if let Some(webhook) = self.snapshot_webhook.as_ref() {
+            webhook.post(format!(
+                "{{\"event\":\"snapshot\",\"timestamp\":\"{}\",\"path\":{},\"text\":{}}}",
+                Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
+                json_string(&path.to_string_lossy()),
+                json_string(&canvas.to_string())
+            ));
+        }
+        if let Some(hook) = self.snapshot_hook.as_ref() {
+            hook.run(path);
+        }
+    }
+
+    /// Confirms a capture without having to read the toast, by ringing the
+    /// terminal bell and flashing the view border when enabled
+    pub fn capture_feedback(&mut self) {
+        if self.bell {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+        if self.flash {
+            self.flash_until = Some(Instant::now() + FLASH_DURATION);
+        }
+    }
+
+    pub fn flashing(&self) -> bool {
+        self.flash_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Opens what was typed or pasted in the open-file prompt
+    pub fn open_path(&mut self, text: &str) {
+        if is_url(text) {
+            self.toasts.push("Only image files can be opened, not URLs");
+            return;
+        }
+
+        let path = clean_path(text);
+        match Source::open_file(&path) {
+            Ok(source) => {
+                self.remember_recent(&path);
+                self.preview = None;
+                self.deinterlace = Deinterlace::load(&source.name());
+                self.source = Some(source);
+                self.fps.reset();
+            }
+            Err(e) => self.toasts.push(format!("Could not open {}", e)),
+        }
+    }
+
+    /// Reopens the file at `index` of the recent ones, `0` being the latest
+    pub fn open_recent(&mut self, index: usize) {
+        if let Some(path) = self.recent.get(index).cloned() {
+            self.open_path(&path);
+        }
+    }
+
+    /// Moves the file to the front of the recent ones, by its full path so
+    /// that it opens from any directory
+    fn remember_recent(&mut self, path: &Path) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let path = path.to_string_lossy().into_owned();
+
+        self.recent.retain(|p| *p != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT);
+        save_recent(&self.recent);
+    }
+
+    pub fn start_burst(&mut self) {
+        self.burst = Some(Burst::new(self.burst_count.max(1), self.burst_interval));
+    }
+
+    /// Saves the frame just drawn when the burst in progress is due for one
+    pub fn poll_burst(&mut self) {
+        if !self.burst.as_mut().is_some_and(|burst| burst.due()) {
+            return;
+        }
+
+        let canvas = match self.last_canvas.as_ref() {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        let result = snapshot::save(canvas, &self.output_dir);
+        if let Ok(path) = result.as_ref() {
+            self.announce_snapshot(path, canvas);
+        }
+
+        let (saved, done) = match self.burst.as_ref() {
+            Some(burst) => (burst.saved, burst.is_done()),
+            None => return,
+        };
+        match result {
+            Ok(_) if done => {
+                self.toasts.push(format!(
+                    "Saved {} snapshots in {}",
+                    saved,
+                    self.output_dir.display()
+                ));
+                self.burst = None;
+                self.capture_feedback();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.toasts
+                    .push(format!("Could not save the snapshot: {}", e));
+                self.burst = None;
+            }
+        }
+    }
+
+    /// Starts a text recording, or finishes the one in progress
+    pub fn toggle_recording(&mut self) {
+        match self.recorder {
+            Some(_) => {
+                self.stop_recording();
+            }
+            None => match Recorder::start(&self.output_dir, self.record_format, &self.settings) {
+                Ok(recorder) => {
+                    self.recorder = Some(recorder);
+                    self.capture_feedback();
+                }
+                Err(e) => self
+                    .toasts
+                    .push(format!("Could not start recording: {}", e)),
+            },
+        }
+    }
+
+    /// Saves the recording and runs its hook, returning whether it was saved
+    pub fn stop_recording(&mut self) -> bool {
+        let recorder = match self.recorder.take() {
+            Some(recorder) => recorder,
+            None => return false,
+        };
+        match recorder.finish() {
+            Ok(path) => {
+                if let Some(hook) = self.recording_hook.as_ref() {
+                    hook.run(&path);
+                }
+                self.toasts.push(format!("Saved {}", path.display()));
+                self.capture_feedback();
+                true
+            }
+            Err(e) => {
+                self.toasts
+                    .push(format!("Could not save the recording: {}", e));
+                false
+            }
+        }
+    }
+
+    pub fn close_camera(&mut self) {
+        self.auto_paused = false;
+        self.stop_recording();
+
+        self.paused = false;
+        self.last_frame = None;
+        self.last_canvas = None;
+        self.render_cache = None;
+        self.replay.clear();
+        self.source = None;
+        self.capture_size = None;
+        self.viewport = Viewport::default();
+        self.drag_origin = None;
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::canvas::Canvas;
+use crate::presets::write_settings;
+use crate::settings::Settings;
+
+/// Extension of the compact recordings
+pub const EXTENSION: &str = "asciivid";
+
+const MAGIC: &[u8; 8] = b"ASCIIVID";
+const VERSION: u8 = 1;
+
+/// Compression level, low enough to keep up with the frame rate
+const LEVEL: i32 = 3;
+
+/// Longest settings header a recording is read with, far more than the
+/// presets format ever writes, so that a damaged length cannot ask for gigabytes
+const MAX_HEADER: usize = 64 * 1024;
+
+/// The stream the frames of a recording are compressed into, which has to be
+/// finished for the recording to be complete
+pub type Writer = zstd::stream::write::Encoder<'static, BufWriter<File>>;
+
+/// A compact recording is the magic and version, the length of the settings
+/// header and the header itself, the look it was recorded with in the format
+/// of the presets, then a single zstd stream of every frame. Each frame is
+/// its time in milliseconds and the length of its glyphs, as 32-bit little
+/// endian numbers, followed by the glyphs, one line per row. Compressing the
+/// frames together lets zstd take advantage of how little changes between them.
+pub fn create(path: &Path, settings: &Settings) -> io::Result<Writer> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    let mut header = String::new();
+    write_settings(&mut header, "recording", settings);
+    file.write_all(MAGIC)?;
+    file.write_all(&[VERSION])?;
+    file.write_all(&(header.len() as u32).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+
+    zstd::stream::write::Encoder::new(file, LEVEL)
+}
+
+pub fn write_frame(writer: &mut impl Write, time: Duration, canvas: &Canvas) -> io::Result<()> {
+    let glyphs = canvas.to_string();
+    writer.write_all(&(time.as_millis() as u32).to_le_bytes())?;
+    writer.write_all(&(glyphs.len() as u32).to_le_bytes())?;
+    writer.write_all(glyphs.as_bytes())
+}
+
+/// Reads the frames of a compact recording, along with their times
+pub fn load(path: &Path) -> Result<Vec<(Duration, Canvas)>, String> {
+    let error = |e: String| format!("{}: {}", path.display(), e);
+    let mut file = File::open(path).map_err(|e| error(e.to_string()))?;
+
+    let mut magic = [0; 9];
+    file.read_exact(&mut magic)
+        .map_err(|_| error(String::from("not a recording")))?;
+    if &magic[..8] != MAGIC {
+        return Err(error(String::from("not a recording")));
+    }
+    if magic[8] != VERSION {
+        return Err(error(format!("unsupported version {}", magic[8])));
+    }
+
+    // the settings are only there for reference
+    let mut length = [0; 4];
+    file.read_exact(&mut length)
+        .map_err(|e| error(e.to_string()))?;
+    let length = u32::from_le_bytes(length) as usize;
+    if length > MAX_HEADER {
+        return Err(error(String::from("the settings header is too long")));
+    }
+    let mut header = vec![0; length];
+    file.read_exact(&mut header)
+        .map_err(|e| error(e.to_string()))?;
+
+    let data = zstd::stream::decode_all(file).map_err(|e| error(e.to_string()))?;
+    let mut frames = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 8 {
+        let time = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        let length = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        // the glyphs are borrowed from the stream, so a length past its end
+        // is never allocated
+        let glyphs = rest
+            .get(8..8usize.saturating_add(length))
+            .ok_or_else(|| error(String::from("the last frame is cut short")))?;
+        let glyphs = String::from_utf8_lossy(glyphs);
+
+        frames.push((
+            Duration::from_millis(time as u64),
+            Canvas::from_text(&glyphs),
+        ));
+        rest = &rest[8 + length..];
+    }
+
+    if frames.is_empty() {
+        return Err(error(String::from("the recording has no frames")));
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf, process};
+
+    /// A file of its own in the temporary directory, removed once the test is done
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            let name = format!("{}-{}.{}", name, process::id(), EXTENSION);
+            TempFile(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn frames_are_read_back() {
+        let file = TempFile::new("round-trip");
+        let frames = vec![
+            (Duration::ZERO, Canvas::from_text("ab\ncd\n")),
+            (Duration::from_millis(40), Canvas::from_text("░▒\n▓█\n")),
+            (Duration::from_millis(95), Canvas::from_text("  \n  \n")),
+        ];
+
+        let mut writer = create(&file.0, &Settings::default()).unwrap();
+        for (time, canvas) in &frames {
+            write_frame(&mut writer, *time, canvas).unwrap();
+        }
+        writer.finish().unwrap().flush().unwrap();
+
+        assert_eq!(load(&file.0).unwrap(), frames);
+    }
+
+    #[test]
+    fn damaged_files_are_errors() {
+        let file = TempFile::new("damaged");
+
+        fs::write(&file.0, b"NOTAVID1").unwrap();
+        assert!(load(&file.0).is_err());
+
+        // a header far longer than any settings
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&file.0, &data).unwrap();
+        assert!(load(&file.0).is_err());
+
+        // a frame claiming more glyphs than there are
+        let mut writer = create(&file.0, &Settings::default()).unwrap();
+        writer.write_all(&0u32.to_le_bytes()).unwrap();
+        writer.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        writer.write_all(b"ab\n").unwrap();
+        writer.finish().unwrap().flush().unwrap();
+        assert!(load(&file.0).is_err());
+
+        // no frames at all
+        let writer = create(&file.0, &Settings::default()).unwrap();
+        writer.finish().unwrap().flush().unwrap();
+        assert!(load(&file.0).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::settings::Settings;
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+/// Levels below this many decibels under full scale read as silence
+pub const FLOOR_DB: f32 = -60.0;
+
+/// How far the loudest sound moves the driven setting by default
+pub const DEFAULT_DEPTH: f32 = 0.5;
+
+/// The rendering parameter that follows the microphone level, for audio-reactive visuals
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reactive {
+    Off,
+    Brightness,
+    Contrast,
+}
+
+impl Reactive {
+    pub fn next(self) -> Reactive {
+        match self {
+            Reactive::Off => Reactive::Brightness,
+            Reactive::Brightness => Reactive::Contrast,
+            Reactive::Contrast => Reactive::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Reactive::Off => "off",
+            Reactive::Brightness => "brightness",
+            Reactive::Contrast => "contrast",
+        }
+    }
+
+    /// Pushes the setting up by `loudness`, in the range [0, 1], times `depth`
+    pub fn apply(self, settings: &mut Settings, loudness: f32, depth: f32) {
+        match self {
+            Reactive::Off => {}
+            Reactive::Brightness => {
+                settings.brightness = (settings.brightness + loudness * depth).clamp(-1.0, 1.0)
+            }
+            Reactive::Contrast => settings.contrast *= 1.0 + 2.0 * loudness * depth,
+        }
+    }
+}
+
+/// Listens to the default microphone and keeps track of how loud it is
+pub struct Microphone {
+    pub name: String,
+    // the stream stops when dropped, so it is kept alongside the level
+    _stream: Stream,
+    /// RMS level of the last buffer, in the range [0, 1], stored as the bits of an f32
+    level: Arc<AtomicU32>,
+}
+
+impl Microphone {
+    pub fn open() -> Result<Microphone, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("There is no microphone")?;
+        let name = device.name().unwrap_or_else(|_| String::from("Microphone"));
+        let supported = device.default_input_config().map_err(|e| e.to_string())?;
+        let format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+
+        let level = Arc::new(AtomicU32::new(0));
+        let stream = match format {
+            SampleFormat::F32 => listen(&device, &config, &level, |s: f32| s),
+            SampleFormat::I16 => listen(&device, &config, &level, |s: i16| {
+                s as f32 / i16::MAX as f32
+            }),
+            SampleFormat::U16 => listen(&device, &config, &level, |s: u16| {
+                (s as f32 - 32768.0) / 32768.0
+            }),
+            other => return Err(format!("Unsupported sample format {:?}", other)),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        Ok(Microphone {
+            name,
+            _stream: stream,
+            level,
+        })
+    }
+
+    /// RMS level of the latest samples, in the range [0, 1]
+    pub fn level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+
+    /// The level in decibels under full scale, down to `FLOOR_DB`
+    pub fn decibels(&self) -> f32 {
+        (20.0 * self.level().max(f32::EPSILON).log10()).max(FLOOR_DB)
+    }
+
+    /// The level on the decibel scale of the meter, from 0 for silence to 1 for full scale
+    pub fn loudness(&self) -> f32 {
+        1.0 - self.decibels() / FLOOR_DB
+    }
+}
+
+fn listen<T: SizedSample>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    level: &Arc<AtomicU32>,
+    to_float: fn(T) -> f32,
+) -> Result<Stream, String> {
+    let level = Arc::clone(level);
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if data.is_empty() {
+                    return;
+                }
+                let sum: f32 = data.iter().map(|s| to_float(*s).powi(2)).sum();
+                let rms = (sum / data.len() as f32).sqrt();
+                level.store(rms.to_bits(), Ordering::Relaxed);
+            },
+            // a glitch in the stream only means a stale level for a moment
+            |_| {},
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
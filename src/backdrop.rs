@@ -0,0 +1,93 @@
+use crate::ansi;
+use crate::canvas::Canvas;
+use image::{imageops, DynamicImage, GrayImage};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Luma difference from the learned background above which a cell belongs to the subject
+const THRESHOLD: u8 = 28;
+
+/// How much of each new frame is blended into the background, letting it
+/// follow slow lighting changes
+const LEARNING_RATE: f32 = 0.05;
+
+/// Time given to step out of the frame before the empty scene is learned
+pub const LEARNING_DELAY: Duration = Duration::from_secs(3);
+
+/// Replaces everything behind the subject with ASCII or ANSI art
+pub struct Backdrop {
+    art: Canvas,
+    /// The empty scene at the resolution of the view, learned once the delay is over
+    background: Option<Vec<f32>>,
+    size: (u32, u32),
+    /// When the next frame is taken as the empty scene
+    learn_at: Instant,
+}
+
+impl Backdrop {
+    pub fn load(path: &Path) -> Result<Backdrop, String> {
+        // an animation ends on its full picture
+        let art = ansi::load(path)?.pop().unwrap_or_else(|| Canvas::new(0, 0));
+        if art.width == 0 || art.height == 0 {
+            return Err(format!("{}: the file is empty", path.display()));
+        }
+
+        Ok(Backdrop {
+            art,
+            background: None,
+            size: (0, 0),
+            learn_at: Instant::now() + LEARNING_DELAY,
+        })
+    }
+
+    /// Seconds left before the empty scene is learned, rounded up
+    pub fn learning_in(&self) -> Option<u64> {
+        let left = self.learn_at.checked_duration_since(Instant::now())?;
+        self.background.is_none().then_some(left.as_secs() + 1)
+    }
+
+    /// Puts the art in every cell of the canvas whose pixel of `image`, which is
+    /// at the size of the canvas, looks like the background
+    pub fn composite(&mut self, image: &DynamicImage, canvas: &mut Canvas) {
+        let gray = image.to_luma8();
+        let size = gray.dimensions();
+
+        let background = match self.background.as_mut() {
+            None if Instant::now() < self.learn_at => return,
+            // the view was resized, scale what was learned along with it
+            Some(background) if self.size != size => {
+                let old = GrayImage::from_fn(self.size.0, self.size.1, |x, y| {
+                    image::Luma([background[(y * self.size.0 + x) as usize] as u8])
+                });
+                let resized =
+                    imageops::resize(&old, size.0, size.1, imageops::FilterType::Triangle);
+                *background = resized.pixels().map(|p| p[0] as f32).collect();
+                self.size = size;
+                background
+            }
+            Some(background) => background,
+            None => {
+                self.background = Some(gray.pixels().map(|p| p[0] as f32).collect());
+                self.size = size;
+                return;
+            }
+        };
+
+        for (x, y, pixel) in gray.enumerate_pixels() {
+            let learned = &mut background[(y * size.0 + x) as usize];
+            if pixel[0].abs_diff(*learned as u8) > THRESHOLD {
+                continue;
+            }
+
+            // only the background is learned, or a still subject would fade into it
+            *learned += (pixel[0] as f32 - *learned) * LEARNING_RATE;
+
+            // the art is tiled when smaller than the view
+            let art_x = x as usize % self.art.width;
+            let art_y = y as usize % self.art.height;
+            canvas.set(x as usize, y as usize, self.art.row(art_y)[art_x]);
+        }
+    }
+}
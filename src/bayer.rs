@@ -0,0 +1,131 @@
+use image::{ImageBuffer, Rgb};
+
+use crate::source::RgbFrame;
+
+/// Order of the color filters over the top-left 2x2 block of a raw sensor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BayerPattern {
+    Rggb,
+    Bggr,
+    Grbg,
+    Gbrg,
+}
+
+impl BayerPattern {
+    pub fn from_name(name: &str) -> Option<BayerPattern> {
+        match name.to_ascii_lowercase().as_str() {
+            "rggb" => Some(BayerPattern::Rggb),
+            "bggr" => Some(BayerPattern::Bggr),
+            "grbg" => Some(BayerPattern::Grbg),
+            "gbrg" => Some(BayerPattern::Gbrg),
+            _ => None,
+        }
+    }
+
+    /// The channel, 0 to 2 for red, green and blue, that a sensor pixel measures
+    fn channel(self, x: u32, y: u32) -> usize {
+        let (red_x, red_y) = match self {
+            BayerPattern::Rggb => (0, 0),
+            BayerPattern::Bggr => (1, 1),
+            BayerPattern::Grbg => (1, 0),
+            BayerPattern::Gbrg => (0, 1),
+        };
+
+        match (x % 2 == red_x, y % 2 == red_y) {
+            (true, true) => 0,
+            (false, false) => 2,
+            _ => 1,
+        }
+    }
+
+    /// Rebuilds a color frame from one byte per sensor pixel, filling in the
+    /// two missing channels of every pixel with the mean of its neighbors
+    /// that measure them
+    pub fn demosaic(self, width: u32, height: u32, raw: &[u8]) -> Result<RgbFrame, String> {
+        if raw.len() < (width * height) as usize {
+            return Err(format!(
+                "a {}x{} raw frame needs {} bytes, the camera sent {}",
+                width,
+                height,
+                width * height,
+                raw.len()
+            ));
+        }
+
+        let at = |x: u32, y: u32| raw[(y * width + x) as usize] as u32;
+
+        Ok(ImageBuffer::from_fn(width, height, |x, y| {
+            let mut sums = [0; 3];
+            let mut counts = [0; 3];
+
+            for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    let channel = self.channel(nx, ny);
+                    sums[channel] += at(nx, ny);
+                    counts[channel] += 1;
+                }
+            }
+
+            let mut pixel = [0; 3];
+            for channel in 0..3 {
+                pixel[channel] = if channel == self.channel(x, y) {
+                    at(x, y) as u8
+                } else {
+                    (sums[channel] / counts[channel].max(1)) as u8
+                };
+            }
+            Rgb(pixel)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: [BayerPattern; 4] = [
+        BayerPattern::Rggb,
+        BayerPattern::Bggr,
+        BayerPattern::Grbg,
+        BayerPattern::Gbrg,
+    ];
+
+    #[test]
+    fn names_are_case_insensitive() {
+        assert_eq!(BayerPattern::from_name("RGGB"), Some(BayerPattern::Rggb));
+        assert_eq!(BayerPattern::from_name("gbrg"), Some(BayerPattern::Gbrg));
+        assert_eq!(BayerPattern::from_name("rgb"), None);
+    }
+
+    #[test]
+    fn an_even_gray_stays_gray() {
+        for pattern in PATTERNS {
+            let frame = pattern.demosaic(4, 3, &[100; 12]).unwrap();
+            assert!(frame.pixels().all(|p| *p == Rgb([100, 100, 100])));
+        }
+    }
+
+    #[test]
+    fn every_pixel_gets_the_channels_of_its_block() {
+        // one block of each pattern measuring red 200, green 100 and blue 50
+        let blocks = [
+            (BayerPattern::Rggb, [200, 100, 100, 50]),
+            (BayerPattern::Bggr, [50, 100, 100, 200]),
+            (BayerPattern::Grbg, [100, 200, 50, 100]),
+            (BayerPattern::Gbrg, [100, 50, 200, 100]),
+        ];
+        for (pattern, raw) in blocks {
+            let frame = pattern.demosaic(2, 2, &raw).unwrap();
+            assert!(
+                frame.pixels().all(|p| *p == Rgb([200, 100, 50])),
+                "{:?}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn short_frames_are_errors() {
+        assert!(BayerPattern::Rggb.demosaic(2, 2, &[0; 3]).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::OnceLock;
+
+/// Width and height of the texture, which tiles the frame
+pub const SIZE: usize = 64;
+
+/// Spread of the filter that measures how crowded a spot is, in pixels
+const SIGMA: f32 = 1.5;
+
+/// Share of the pixels set in the pattern the ranking starts from
+const INITIAL_SHARE: usize = 10;
+
+/// The rank of every pixel of the texture, from 0 to SIZE² - 1, computed
+/// once on first use
+pub fn texture() -> &'static [u16] {
+    static TEXTURE: OnceLock<Vec<u16>> = OnceLock::new();
+    TEXTURE.get_or_init(void_and_cluster)
+}
+
+/// Ulichney's void-and-cluster method: pixels are ranked in the order that
+/// keeps the pixels set so far as evenly spread as possible, so that any
+/// threshold of the ranks gives a pattern without clumps or low-frequency
+/// structure. The texture always comes out the same, the random start being seeded.
+fn void_and_cluster() -> Vec<u16> {
+    let count = SIZE * SIZE;
+
+    // how much a set pixel adds to the crowding around it, wrapping around
+    // the edges so that the texture tiles seamlessly
+    let mut filter = vec![0.0; count];
+    for dy in 0..SIZE {
+        for dx in 0..SIZE {
+            let wrap = |d: usize| d.min(SIZE - d) as f32;
+            let distance = wrap(dx).powi(2) + wrap(dy).powi(2);
+            filter[dy * SIZE + dx] = (-distance / (2.0 * SIGMA * SIGMA)).exp();
+        }
+    }
+
+    let mut pattern = Pattern {
+        set: vec![false; count],
+        energy: vec![0.0; count],
+        filter,
+    };
+
+    // a random start, then moved from its most crowded spots to its emptiest
+    // until it is as even as it gets
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut placed = 0;
+    while placed < count / INITIAL_SHARE {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let i = (state % count as u64) as usize;
+        if !pattern.set[i] {
+            pattern.toggle(i);
+            placed += 1;
+        }
+    }
+    loop {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        if void == cluster {
+            pattern.toggle(void);
+            break;
+        }
+        pattern.toggle(void);
+    }
+
+    let mut ranks = vec![0; count];
+    let initial = pattern.clone();
+
+    // the pixels of the start are ranked below it by taking them away
+    for rank in (0..placed).rev() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        ranks[cluster] = rank as u16;
+    }
+
+    // and the others above it by filling in the voids
+    let mut pattern = initial;
+    for rank in placed..count {
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        ranks[void] = rank as u16;
+    }
+
+    ranks
+}
+
+#[derive(Clone)]
+struct Pattern {
+    set: Vec<bool>,
+    /// How crowded every pixel is by the set pixels around it
+    energy: Vec<f32>,
+    filter: Vec<f32>,
+}
+
+impl Pattern {
+    fn toggle(&mut self, i: usize) {
+        self.set[i] = !self.set[i];
+        let sign = if self.set[i] { 1.0 } else { -1.0 };
+        let (x, y) = (i % SIZE, i / SIZE);
+        for (j, energy) in self.energy.iter_mut().enumerate() {
+            let dx = (j % SIZE + SIZE - x) % SIZE;
+            let dy = (j / SIZE + SIZE - y) % SIZE;
+            *energy += sign * self.filter[dy * SIZE + dx];
+        }
+    }
+
+    /// The set pixel with the most set pixels around it
+    fn tightest_cluster(&self) -> usize {
+        self.pick(true, |a, b| a > b)
+    }
+
+    /// The unset pixel furthest from the set ones
+    fn largest_void(&self) -> usize {
+        self.pick(false, |a, b| a < b)
+    }
+
+    fn pick(&self, set: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+        let mut best = None;
+        for (i, &energy) in self.energy.iter().enumerate() {
+            if self.set[i] == set && best.is_none_or(|(_, e)| better(energy, e)) {
+                best = Some((i, energy));
+            }
+        }
+        best.map_or(0, |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_ranks_are_a_permutation() {
+        let mut ranks = texture().to_vec();
+        ranks.sort_unstable();
+        assert_eq!(ranks, (0..(SIZE * SIZE) as u16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn thresholds_average_a_half() {
+        let texture = texture();
+        let mean = texture
+            .iter()
+            .map(|&rank| (rank as f32 + 0.5) / texture.len() as f32)
+            .sum::<f32>()
+            / texture.len() as f32;
+        assert!((mean - 0.5).abs() < 1e-3, "{}", mean);
+
+        // and so does every quarter of the tile, the noise having no clumps
+        let quarter = SIZE / 2;
+        for (qx, qy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let sum: f32 = (0..quarter)
+                .flat_map(|y| (0..quarter).map(move |x| (qx * quarter + x, qy * quarter + y)))
+                .map(|(x, y)| texture[y * SIZE + x] as f32 / texture.len() as f32)
+                .sum();
+            let mean = sum / (quarter * quarter) as f32;
+            assert!((mean - 0.5).abs() < 0.05, "{}", mean);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use image::DynamicImage;
+use tui::layout::Rect;
+
+use crate::canvas::{Canvas, Cell};
+use crate::dither::{luma, normalize};
+use crate::quantize::quantizer;
+use crate::resample;
+use crate::settings::Settings;
+
+/// Dots of a braille character across and down
+const DOTS_WIDE: u32 = 2;
+const DOTS_HIGH: u32 = 4;
+
+/// The bit of every dot in the braille block, by row and column, in the
+/// numbering of the standard rather than reading order
+const DOT_BITS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// First character of the braille block, the one without any dot raised
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Draws the image as braille characters, every cell holding 2x4 pixels that
+/// are either lit or not, dithered with the algorithm of the settings
+pub fn render(image: &DynamicImage, view: Rect, settings: &Settings) -> Canvas {
+    let (width, height) = (
+        view.width as u32 * DOTS_WIDE,
+        view.height as u32 * DOTS_HIGH,
+    );
+    let image = resample::resize(image, width, height, settings.filter);
+    let gray = normalize(&luma(&image, settings.luma), settings);
+
+    let values: Vec<f32> = gray.pixels().map(|p| p[0] as f32 / 255.0).collect();
+    let lit = quantizer(settings.algorithm).quantize(&values, width as usize, 1.0);
+
+    let mut canvas = Canvas::new(view.width as usize, view.height as usize);
+    for row in 0..view.height as u32 {
+        for column in 0..view.width as u32 {
+            let mut bits = 0;
+            for (dy, row_bits) in DOT_BITS.iter().enumerate() {
+                for (dx, bit) in row_bits.iter().enumerate() {
+                    let x = column * DOTS_WIDE + dx as u32;
+                    let y = row * DOTS_HIGH + dy as u32;
+                    if lit[(y * width + x) as usize] == 1 {
+                        bits |= bit;
+                    }
+                }
+            }
+            let ch = char::from_u32(BRAILLE_BLANK + bits).unwrap_or(' ');
+            canvas.set(column as usize, row as usize, Cell::new(ch));
+        }
+    }
+    canvas
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+};
+
+/// A single glyph of the rendered image
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Cell {
+    pub fn new(ch: char) -> Cell {
+        Cell {
+            ch,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    fn style(&self) -> Style {
+        let mut style = Style::default();
+        if let Some(fg) = self.fg {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = self.bg {
+            style = style.bg(bg);
+        }
+        style
+    }
+}
+
+/// The glyph grid a frame is rendered to, which overlays can then draw over
+#[derive(Clone, PartialEq, Debug)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![Cell::new(' '); width * height],
+        }
+    }
+
+    /// Plain glyphs, one line per row, as written by its `Display`
+    pub fn from_text(text: &str) -> Canvas {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut canvas = Canvas::new(width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                canvas.set(x, y, Cell::new(ch));
+            }
+        }
+        canvas
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
+        if x < self.width && y < self.height {
+            self.cells.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces a glyph, ignoring positions outside the canvas
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        if let Some(c) = self.get_mut(x, y) {
+            *c = cell;
+        }
+    }
+
+    /// Copies the left half of another canvas of the same size over this one
+    pub fn paste_left_half(&mut self, other: &Canvas) {
+        if (other.width, other.height) != (self.width, self.height) {
+            return;
+        }
+        for y in 0..self.height {
+            let start = y * self.width;
+            let end = start + self.width / 2;
+            self.cells[start..end].copy_from_slice(&other.cells[start..end]);
+        }
+    }
+
+    /// The top-left part of the canvas at the given size, padded with blanks when smaller
+    pub fn crop(&self, width: usize, height: usize) -> Canvas {
+        let mut cropped = Canvas::new(width, height);
+        for y in 0..height.min(self.height) {
+            for (x, cell) in self.row(y).iter().take(width).enumerate() {
+                cropped.set(x, y, *cell);
+            }
+        }
+        cropped
+    }
+
+    pub fn row(&self, y: usize) -> &[Cell] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Builds the styled text drawn by the viewer, merging runs of equally styled cells
+    pub fn to_text(&self) -> Text<'static> {
+        let mut lines = Vec::with_capacity(self.height);
+
+        for y in 0..self.height {
+            let mut spans = Vec::new();
+            let mut run = String::new();
+            let mut run_style: Option<Style> = None;
+
+            for cell in self.row(y) {
+                let style = cell.style();
+                if run_style != Some(style) {
+                    if let Some(s) = run_style {
+                        spans.push(Span::styled(std::mem::take(&mut run), s));
+                    }
+                    run_style = Some(style);
+                }
+                run.push(cell.ch);
+            }
+            if let Some(s) = run_style {
+                spans.push(Span::styled(run, s));
+            }
+
+            lines.push(Spans::from(spans));
+        }
+
+        Text::from(lines)
+    }
+}
+
+/// The plain glyphs, one line per row
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            let line: String = self.row(y).iter().map(|c| c.ch).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
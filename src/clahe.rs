@@ -0,0 +1,80 @@
+use image::{DynamicImage, GrayImage};
+
+/// The frame is equalized in this many tiles across and down
+const TILES: u32 = 8;
+
+/// How many times the average count a histogram bin may hold before the
+/// excess is spread over the other bins, which keeps noise in flat areas
+/// from being amplified
+const CLIP_LIMIT: f32 = 2.0;
+
+/// Contrast-limited adaptive histogram equalization: equalizes every tile of
+/// the frame on its own, so that a face in front of a bright window gets its
+/// own share of the ramp
+pub fn equalize(image: &DynamicImage) -> DynamicImage {
+    let gray = image.to_luma8();
+    let (width, height) = gray.dimensions();
+    let tiles_x = TILES.min(width).max(1);
+    let tiles_y = TILES.min(height).max(1);
+    let tile_width = width.div_ceil(tiles_x);
+    let tile_height = height.div_ceil(tiles_y);
+
+    let mut maps = Vec::with_capacity((tiles_x * tiles_y) as usize);
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let x0 = tx * tile_width;
+            let y0 = ty * tile_height;
+            let x1 = (x0 + tile_width).min(width);
+            let y1 = (y0 + tile_height).min(height);
+            maps.push(tile_map(&gray, x0, y0, x1, y1));
+        }
+    }
+
+    // blend the maps of the four closest tile centers, or the tiles would show as blocks
+    let output = GrayImage::from_fn(width, height, |x, y| {
+        let level = gray.get_pixel(x, y)[0] as usize;
+
+        let fx = ((x as f32 + 0.5) / tile_width as f32 - 0.5).clamp(0.0, (tiles_x - 1) as f32);
+        let fy = ((y as f32 + 0.5) / tile_height as f32 - 0.5).clamp(0.0, (tiles_y - 1) as f32);
+        let (tx0, ty0) = (fx.floor() as u32, fy.floor() as u32);
+        let (tx1, ty1) = ((tx0 + 1).min(tiles_x - 1), (ty0 + 1).min(tiles_y - 1));
+        let (ax, ay) = (fx - tx0 as f32, fy - ty0 as f32);
+
+        let map = |tx: u32, ty: u32| maps[(ty * tiles_x + tx) as usize][level];
+        let top = map(tx0, ty0) * (1.0 - ax) + map(tx1, ty0) * ax;
+        let bottom = map(tx0, ty1) * (1.0 - ax) + map(tx1, ty1) * ax;
+
+        image::Luma([(top * (1.0 - ay) + bottom * ay).round() as u8])
+    });
+
+    DynamicImage::ImageLuma8(output)
+}
+
+/// Builds the clipped equalization curve of a tile
+fn tile_map(gray: &GrayImage, x0: u32, y0: u32, x1: u32, y1: u32) -> [f32; 256] {
+    let mut histogram = [0f32; 256];
+    for y in y0..y1 {
+        for x in x0..x1 {
+            histogram[gray.get_pixel(x, y)[0] as usize] += 1.0;
+        }
+    }
+
+    let count = ((x1 - x0) * (y1 - y0)).max(1) as f32;
+    let limit = (CLIP_LIMIT * count / 256.0).max(1.0);
+    let mut excess = 0.0;
+    for bin in histogram.iter_mut() {
+        if *bin > limit {
+            excess += *bin - limit;
+            *bin = limit;
+        }
+    }
+    let share = excess / 256.0;
+
+    let mut map = [0f32; 256];
+    let mut total = 0.0;
+    for (level, bin) in histogram.iter().enumerate() {
+        total += bin + share;
+        map[level] = total / count * 255.0;
+    }
+    map
+}
@@ -0,0 +1,106 @@
+use image::{DynamicImage, Rgb};
+use tui::style::Color;
+
+use crate::canvas::Canvas;
+use crate::settings::{ColorMode, Settings};
+
+/// The levels of each channel in the 6x6x6 color cube of the xterm palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 system colors the palette starts with, as xterm draws them
+const SYSTEM: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// Colors every glyph of the canvas with the pixel of the frame it stands for,
+/// the frame having been scaled to one pixel per cell
+pub fn colorize(canvas: &mut Canvas, frame: &DynamicImage, settings: &Settings) {
+    let color: fn([u8; 3]) -> Color = match settings.color_mode {
+        ColorMode::Ansi256 => |rgb| Color::Indexed(closest_xterm(rgb)),
+        ColorMode::Truecolor => |[r, g, b]| Color::Rgb(r, g, b),
+        ColorMode::Mono | ColorMode::HalfBlock => return,
+    };
+
+    let frame = frame.to_rgb8();
+    for (x, y, &Rgb(rgb)) in frame.enumerate_pixels() {
+        let rgb = match settings.color_boost {
+            true => boost(rgb),
+            false => rgb,
+        };
+        if let Some(cell) = canvas.get_mut(x as usize, y as usize) {
+            cell.fg = Some(color(rgb));
+        }
+    }
+}
+
+/// Scales the color up until its brightest channel is full, keeping its hue
+/// and saturation; a dark cell is already drawn with a sparse glyph, and in
+/// a dark color on top of that it would all but disappear
+fn boost(rgb: [u8; 3]) -> [u8; 3] {
+    let max = rgb.into_iter().max().unwrap_or(0);
+    if max == 0 {
+        return rgb;
+    }
+    rgb.map(|c| (c as u32 * 255 / max as u32) as u8)
+}
+
+/// The entry of the xterm 256-color palette closest to the color, from the
+/// color cube or the gray ramp; the system colors are left out, since every
+/// terminal theme draws them differently
+pub fn closest_xterm(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(cube_index);
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    // the 24 grays from 8 to 238, in steps of 10
+    let average = rgb.iter().map(|&c| c as u32).sum::<u32>() / 3;
+    let gray = 232 + ((average.saturating_sub(3)) / 10).min(23) as u8;
+
+    if distance(rgb, xterm_rgb(gray)) < distance(rgb, xterm_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The RGB value xterm draws an entry of its 256-color palette in
+pub fn xterm_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => SYSTEM[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            [i / 36, i / 6 % 6, i % 6].map(|level| CUBE_LEVELS[level as usize])
+        }
+        _ => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// The level of the color cube closest to a channel
+fn cube_index(channel: u8) -> u8 {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, &level)| (level as i32 - channel as i32).abs())
+        .map_or(0, |(i, _)| i as u8)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> i32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (a as i32 - b as i32).pow(2))
+        .sum()
+}
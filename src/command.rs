@@ -0,0 +1,92 @@
+use crate::settings::{Charset, TARGET_FPS};
+
+/// A `:` command of the vim-style key scheme
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Quit,
+    /// Saves the glyphs of the current frame to the output directory
+    Snapshot,
+    /// Replaces the character ramp, ordered from dark to light
+    Charset(Charset),
+    Fps(u32),
+    Reset,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        // the ramp may start or end with spaces, so it is taken as typed
+        if let Some(ramp) = line.strip_prefix("charset ") {
+            return Charset::parse("custom", ramp).map(Command::Charset);
+        }
+
+        let mut words = line.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (Some("q" | "quit"), None) => Command::Quit,
+            (Some("snapshot"), None) => Command::Snapshot,
+            (Some("reset"), None) => Command::Reset,
+            // only the rates the settings panel steps through
+            (Some("fps"), Some(fps)) => match fps.parse() {
+                Ok(fps) if TARGET_FPS.contains(&fps) => Command::Fps(fps),
+                _ => {
+                    let rates: Vec<String> = TARGET_FPS.iter().map(u32::to_string).collect();
+                    return Err(format!(
+                        "Not a frame rate: {}, pick one of {}",
+                        fps,
+                        rates.join(", ")
+                    ));
+                }
+            },
+            (Some(name), _) => return Err(format!("Not a command: {}", name)),
+            (None, _) => return Err(String::from("No command given")),
+        };
+
+        match words.next() {
+            Some(_) => Err(format!("Too many arguments: {}", line)),
+            None => Ok(command),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_commands() {
+        assert_eq!(Command::parse("q"), Ok(Command::Quit));
+        assert_eq!(Command::parse("quit"), Ok(Command::Quit));
+        assert_eq!(Command::parse(" snapshot "), Ok(Command::Snapshot));
+        assert_eq!(Command::parse("reset"), Ok(Command::Reset));
+        assert_eq!(Command::parse("fps 30"), Ok(Command::Fps(30)));
+    }
+
+    #[test]
+    fn keeps_the_spaces_of_a_ramp() {
+        assert_eq!(
+            Command::parse("charset  .:@"),
+            Ok(Command::Charset(Charset::new("custom", " .:@")))
+        );
+        assert!(Command::parse("charset @").is_err());
+    }
+
+    #[test]
+    fn only_accepts_the_listed_frame_rates() {
+        for fps in TARGET_FPS {
+            assert_eq!(
+                Command::parse(&format!("fps {}", fps)),
+                Ok(Command::Fps(fps))
+            );
+        }
+        assert!(Command::parse("fps 7").is_err());
+        assert!(Command::parse("fps fast").is_err());
+        assert!(Command::parse("fps").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_extra_arguments() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("save").is_err());
+        assert!(Command::parse("quit now").is_err());
+        assert!(Command::parse("fps 30 60").is_err());
+    }
+}
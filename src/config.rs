@@ -0,0 +1,261 @@
+use std::{env, fs, path::PathBuf};
+
+const CONFIG_FILE: &str = "config.toml";
+const LAST_CAMERA_FILE: &str = "last_camera";
+const CELL_ASPECT_FILE: &str = "cell_aspect";
+const RECENT_FILE: &str = "recent";
+
+/// How many of the files opened last are remembered
+pub const MAX_RECENT: usize = 9;
+
+/// Directory holding the configuration file and the remembered state
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(base.join("ascii-camera-dithering"))
+}
+
+/// The user configuration, a small subset of TOML: `[section]` headers and
+/// `key = value` pairs whose values are strings, numbers or booleans
+#[derive(Default)]
+pub struct Config {
+    /// Keys are prefixed by their section, e.g. `theme.highlight`
+    values: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Loads the configuration file, an absent file being an empty configuration
+    pub fn load() -> Result<Config, String> {
+        let path = match config_dir() {
+            Some(dir) => dir.join(CONFIG_FILE),
+            None => return Ok(Config::default()),
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut values = Vec::new();
+        let mut section = String::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(format!("line {}: expected `key = value`", number + 1)),
+            };
+
+            let value = parse_value(value).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            let key = if section.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", section, key)
+            };
+
+            values.push((key, value));
+        }
+
+        Ok(Config { values })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        // later definitions win, like in most config formats
+        self.values
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+
+    /// The keys of a section with their values, in the order they first
+    /// appear, for sections whose keys are names chosen by the user
+    pub fn section(&self, name: &str) -> Vec<(&str, &str)> {
+        let prefix = format!("{}.", name);
+        let mut entries: Vec<(&str, &str)> = Vec::new();
+        for (key, _) in &self.values {
+            let key = match key.strip_prefix(&prefix) {
+                Some(key) => key,
+                None => continue,
+            };
+            if !entries.iter().any(|(k, _)| *k == key) {
+                entries.push((key, self.get(&format!("{}{}", prefix, key)).unwrap_or("")));
+            }
+        }
+        entries
+    }
+}
+
+/// Unquotes a string value and strips trailing comments from bare values
+fn parse_value(value: &str) -> Result<String, String> {
+    let rest = match value.strip_prefix('"') {
+        Some(rest) => rest,
+        None => {
+            let bare = value.split('#').next().unwrap_or("").trim();
+            return Ok(bare.to_string());
+        }
+    };
+
+    let mut output = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(output),
+            '\\' => match chars.next() {
+                Some('n') => output.push('\n'),
+                Some('t') => output.push('\t'),
+                Some(c) => output.push(c),
+                None => break,
+            },
+            c => output.push(c),
+        }
+    }
+
+    Err(String::from("unterminated string"))
+}
+
+/// Writes a string value the way `parse_value` reads it back
+pub fn quote(value: &str) -> String {
+    let mut output = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                output.push('\\');
+                output.push(c);
+            }
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Returns the name of the camera used last time, if any
+pub fn load_last_camera() -> Option<String> {
+    let path = config_dir()?.join(LAST_CAMERA_FILE);
+    let name = fs::read_to_string(path).ok()?;
+
+    Some(name.trim_end().to_string())
+}
+
+pub fn save_last_camera(name: &str) {
+    let dir = match config_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    // remembering the camera is a convenience, failing to do so is not an error
+    if fs::create_dir_all(&dir).is_ok() {
+        let _ = fs::write(dir.join(LAST_CAMERA_FILE), name);
+    }
+}
+
+/// Returns the files opened last, one per line, the latest first
+pub fn load_recent() -> Vec<String> {
+    let text = match config_dir().map(|dir| fs::read_to_string(dir.join(RECENT_FILE))) {
+        Some(Ok(text)) => text,
+        _ => return Vec::new(),
+    };
+
+    text.lines()
+        .filter(|line| !line.is_empty())
+        .take(MAX_RECENT)
+        .map(String::from)
+        .collect()
+}
+
+pub fn save_recent(recent: &[String]) {
+    let dir = match config_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    // like the last camera, the list is a convenience
+    if fs::create_dir_all(&dir).is_ok() {
+        let _ = fs::write(dir.join(RECENT_FILE), recent.join("\n"));
+    }
+}
+
+/// Returns how many times taller than wide the terminal cells were calibrated to be
+pub fn load_cell_aspect() -> Option<f32> {
+    let path = config_dir()?.join(CELL_ASPECT_FILE);
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+pub fn save_cell_aspect(aspect: f32) -> Result<(), String> {
+    let dir = config_dir().ok_or("No configuration directory")?;
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(dir.join(CELL_ASPECT_FILE), aspect.to_string()))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_keys_with_their_section() {
+        let config = Config::parse("top = 1\n\n[theme]\n# a comment\nname = \"light\"").unwrap();
+        assert_eq!(config.get("top"), Some("1"));
+        assert_eq!(config.get("theme.name"), Some("light"));
+        assert_eq!(config.get("name"), None);
+    }
+
+    #[test]
+    fn reads_the_values() {
+        let config = Config::parse(
+            "quoted = \"a \\\"b\\\"\\n\\tc # d\"\nbare = 42 # the answer\nflag = true",
+        )
+        .unwrap();
+        assert_eq!(config.get("quoted"), Some("a \"b\"\n\tc # d"));
+        assert_eq!(config.get("bare"), Some("42"));
+        assert_eq!(config.get_bool("flag"), Some(true));
+        assert_eq!(config.get_bool("bare"), None);
+    }
+
+    #[test]
+    fn later_definitions_win() {
+        let config = Config::parse("[a]\nx = 1\ny = 2\n[a]\nx = 3").unwrap();
+        assert_eq!(config.get("a.x"), Some("3"));
+        assert_eq!(config.section("a"), vec![("x", "3"), ("y", "2")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        assert_eq!(
+            Config::parse("a = 1\nb").err(),
+            Some(String::from("line 2: expected `key = value`"))
+        );
+        assert_eq!(
+            Config::parse("a = \"open").err(),
+            Some(String::from("line 1: unterminated string"))
+        );
+    }
+
+    #[test]
+    fn quote_is_read_back() {
+        let value = "say \"hi\"\\\n\tthere";
+        let config = Config::parse(&format!("key = {}", quote(value))).unwrap();
+        assert_eq!(config.get("key"), Some(value));
+    }
+}
@@ -0,0 +1,103 @@
+use image::{ImageBuffer, Rgb};
+use std::fs;
+
+use crate::config::config_dir;
+use crate::source::RgbFrame;
+
+/// Remembers the mode of every source it was turned on for, one `mode name` line each
+const DEINTERLACE_FILE: &str = "deinterlace";
+
+/// How the combing of interlaced sources, such as analog capture cards, is removed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Deinterlace {
+    Off,
+    /// Keeps a single field and doubles its lines
+    Bob,
+    /// Keeps a single field and interpolates the lines in between
+    Linear,
+}
+
+impl Deinterlace {
+    pub const ALL: [Deinterlace; 3] = [Deinterlace::Off, Deinterlace::Bob, Deinterlace::Linear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Deinterlace::Off => "off",
+            Deinterlace::Bob => "bob",
+            Deinterlace::Linear => "linear",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Deinterlace> {
+        Deinterlace::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn next(self) -> Deinterlace {
+        match self {
+            Deinterlace::Off => Deinterlace::Bob,
+            Deinterlace::Bob => Deinterlace::Linear,
+            Deinterlace::Linear => Deinterlace::Off,
+        }
+    }
+
+    pub fn apply(self, frame: RgbFrame) -> RgbFrame {
+        let (width, height) = frame.dimensions();
+        if self == Deinterlace::Off || height < 2 {
+            return frame;
+        }
+
+        // the even lines make up the field that is kept
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let above = y & !1;
+            if y == above || self == Deinterlace::Bob {
+                return *frame.get_pixel(x, above);
+            }
+
+            let below = (y + 1).min(height - 1) & !1;
+            let (a, b) = (frame.get_pixel(x, above), frame.get_pixel(x, below));
+            Rgb([0, 1, 2].map(|c| ((a[c] as u16 + b[c] as u16) / 2) as u8))
+        })
+    }
+
+    /// Returns the mode remembered for a source, off if there is none
+    pub fn load(source: &str) -> Deinterlace {
+        load_all()
+            .into_iter()
+            .find(|(_, name)| name == source)
+            .map(|(mode, _)| mode)
+            .unwrap_or(Deinterlace::Off)
+    }
+
+    /// Remembers the mode for a source
+    pub fn save(self, source: &str) -> Result<(), String> {
+        let mut modes = load_all();
+        modes.retain(|(_, name)| name != source);
+        if self != Deinterlace::Off {
+            modes.push((self, source.to_string()));
+        }
+
+        let text: String = modes
+            .iter()
+            .map(|(mode, name)| format!("{} {}\n", mode.name(), name))
+            .collect();
+
+        let dir = config_dir().ok_or("Could not find the configuration directory")?;
+        fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(dir.join(DEINTERLACE_FILE), text))
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn load_all() -> Vec<(Deinterlace, String)> {
+    let text = match config_dir().map(|dir| fs::read_to_string(dir.join(DEINTERLACE_FILE))) {
+        Some(Ok(text)) => text,
+        _ => return Vec::new(),
+    };
+
+    text.lines()
+        .filter_map(|line| {
+            let (mode, name) = line.split_once(' ')?;
+            Some((Deinterlace::from_name(mode)?, name.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,75 @@
+/// What a dialog was opened for, which decides what its options do
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DialogKind {
+    /// Opening the selected camera failed, offers to retry or go back
+    CameraError,
+    /// Asks before deleting the snapshot highlighted in the gallery
+    DeleteSnapshot,
+    /// Quitting with a recording in progress, asks whether to keep it
+    QuitWhileRecording,
+    /// Shows the text of a scanned QR code
+    QrCode,
+}
+
+/// A modal popup with a message and a row of options to choose from
+pub struct Dialog {
+    pub kind: DialogKind,
+    pub title: &'static str,
+    pub message: String,
+    pub options: &'static [&'static str],
+    pub selected: usize,
+}
+
+impl Dialog {
+    pub fn camera_error(message: String) -> Dialog {
+        Dialog {
+            kind: DialogKind::CameraError,
+            title: "Could not open the camera",
+            message,
+            options: &["Retry", "Back"],
+            selected: 0,
+        }
+    }
+
+    pub fn delete_snapshot(name: &str) -> Dialog {
+        Dialog {
+            kind: DialogKind::DeleteSnapshot,
+            title: "Delete snapshot",
+            message: format!("Delete {}? This cannot be undone.", name),
+            options: &["Cancel", "Delete"],
+            selected: 0,
+        }
+    }
+
+    pub fn quit_while_recording() -> Dialog {
+        Dialog {
+            kind: DialogKind::QuitWhileRecording,
+            title: "Recording in progress",
+            message: String::from("Save the recording before quitting?"),
+            options: &["Save", "Discard", "Cancel"],
+            selected: 0,
+        }
+    }
+
+    pub fn qr_code(text: String) -> Dialog {
+        Dialog {
+            kind: DialogKind::QrCode,
+            title: "QR code",
+            message: text,
+            options: &["Copy", "Close"],
+            selected: 0,
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.options.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.options.len() - 1) % self.options.len();
+    }
+
+    pub fn choice(&self) -> &'static str {
+        self.options[self.selected]
+    }
+}
@@ -0,0 +1,305 @@
+use image::{DynamicImage, GenericImageView, GrayImage, ImageBuffer};
+
+use crate::canvas::{Canvas, Cell};
+use crate::effects::crt;
+use crate::quantize::quantizer;
+use crate::settings::{DitherAlgorithm, Exposure, LumaWeights, Settings, ToneMap};
+
+/// How far past the boundary between two characters of the ramp a cell's
+/// luma has to move before its glyph changes, in steps of the ramp
+const HYSTERESIS_BAND: f32 = 0.15;
+
+/// The ramp levels of the last frame, which keep cells whose luma sits right
+/// on the boundary between two characters from flipping between them every frame
+#[derive(Default)]
+pub struct Hysteresis {
+    levels: Vec<u8>,
+    width: u32,
+    ramp_len: usize,
+}
+
+impl Hysteresis {
+    /// Picks the level of a cell, holding on to the last one unless the value
+    /// moved clearly past the boundary
+    fn level(&mut self, index: usize, value: f32) -> u8 {
+        let previous = self.levels[index];
+        if (value - previous as f32).abs() <= 0.5 + HYSTERESIS_BAND {
+            return previous;
+        }
+        let level = value.round() as u8;
+        self.levels[index] = level;
+        level
+    }
+
+    /// Starts over when the view or the ramp changed, since the levels no
+    /// longer line up with the cells or the characters
+    fn prepare(&mut self, width: u32, height: u32, ramp_len: usize, values: &[f32]) {
+        if self.width != width
+            || self.levels.len() != (width * height) as usize
+            || self.ramp_len != ramp_len
+        {
+            self.levels = values.iter().map(|v| v.round() as u8).collect();
+            self.width = width;
+            self.ramp_len = ramp_len;
+        }
+    }
+}
+
+pub fn dither_image(
+    image: DynamicImage,
+    settings: &Settings,
+    hysteresis: Option<&mut Hysteresis>,
+) -> Canvas {
+    let (width, height) = image.dimensions();
+    let ramp = &settings.charset.ramp;
+
+    let binding = luma(&image, settings.luma);
+    let image = &binding;
+
+    let mut ascii_image = Canvas::new(width as usize, height as usize);
+
+    let norm_image = normalize(image, settings);
+
+    // scale the image to the range of ASCII characters
+    let values: Vec<f32> = norm_image
+        .pixels()
+        .map(|pixel| settings.charset.position(pixel[0] as f32 / 255.0))
+        .collect();
+    let levels: Vec<u8> = match (settings.algorithm, hysteresis) {
+        (DitherAlgorithm::Threshold, Some(hysteresis)) => {
+            hysteresis.prepare(width, height, ramp.len(), &values);
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| hysteresis.level(i, *value))
+                .collect()
+        }
+        // holding on to the levels of the other algorithms would leave their
+        // error or pattern nowhere to go
+        (algorithm, _) => {
+            quantizer(algorithm).quantize(&values, width as usize, (ramp.len() - 1) as f32)
+        }
+    };
+
+    // replace the pixel values with their corresponding ASCII characters
+    for (i, level) in levels.into_iter().enumerate() {
+        let (x, y) = (i % width as usize, i / width as usize);
+        let edge = match settings.edges {
+            true => edge_glyph(&norm_image, x as u32, y as u32),
+            false => None,
+        };
+        ascii_image.set(x, y, Cell::new(edge.unwrap_or(ramp[level as usize])));
+    }
+
+    ascii_image
+}
+
+/// Gradients weaker than this share of the strongest one the Sobel operator
+/// can find are not edges, and keep their glyph of the ramp
+const EDGE_THRESHOLD: f32 = 0.25;
+
+/// Picks the line that runs along the edge the pixel lies on, from the
+/// direction of the luma gradient, `None` when it is not on a strong edge
+fn edge_glyph(image: &GrayImage, x: u32, y: u32) -> Option<char> {
+    let (width, height) = image.dimensions();
+    // the border pixels repeat outwards
+    let luma = |dx: i32, dy: i32| {
+        let x = (x as i32 + dx).clamp(0, width as i32 - 1);
+        let y = (y as i32 + dy).clamp(0, height as i32 - 1);
+        image.get_pixel(x as u32, y as u32)[0] as f32
+    };
+
+    let gx = luma(1, -1) + 2.0 * luma(1, 0) + luma(1, 1)
+        - luma(-1, -1)
+        - 2.0 * luma(-1, 0)
+        - luma(-1, 1);
+    let gy = luma(-1, 1) + 2.0 * luma(0, 1) + luma(1, 1)
+        - luma(-1, -1)
+        - 2.0 * luma(0, -1)
+        - luma(1, -1);
+    if gx.hypot(gy) < EDGE_THRESHOLD * 4.0 * 255.0 {
+        return None;
+    }
+
+    // the edge runs across the gradient; with y pointing down, a line that
+    // rises to the right is at 135 degrees
+    let angle = (gy.atan2(gx).to_degrees() + 90.0).rem_euclid(180.0);
+    let glyph = match angle {
+        a if !(22.5..157.5).contains(&a) => {
+            // the line sits on the brighter side, which for `_` is below
+            if gy > 0.0 {
+                '_'
+            } else {
+                '-'
+            }
+        }
+        a if a < 67.5 => '\\',
+        a if a < 112.5 => '|',
+        _ => '/',
+    };
+    Some(glyph)
+}
+
+/// Stretches the luma to the full range, unless auto-exposure already brought
+/// it to a good level, and applies the adjustments of the settings to it
+pub fn normalize(image: &GrayImage, settings: &Settings) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let (min, max) = match settings.exposure {
+        Exposure::Normalize => percentiles(image.as_raw(), settings.clip),
+        Exposure::Auto => (0, 255),
+    };
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let pixel = image.get_pixel(x, y);
+        let value = (pixel[0] as f32 - min as f32) / (max - min).max(1) as f32;
+        let value = adjust(value.clamp(0.0, 1.0), settings);
+        let value = if settings.crt {
+            value * crt(x, y, width, height)
+        } else {
+            value
+        };
+        image::Luma([(value * 255.0) as u8])
+    })
+}
+
+/// Finds the levels below and above which `clip` percent of the pixels fall
+fn percentiles(pixels: &[u8], clip: f32) -> (u8, u8) {
+    let mut histogram = [0usize; 256];
+    for &pixel in pixels {
+        histogram[pixel as usize] += 1;
+    }
+
+    let clipped = (pixels.len() as f32 * clip / 100.0) as usize;
+    let min = first_past(&histogram, 0..256, clipped);
+    let max = first_past(&histogram, (0..256).rev(), clipped);
+
+    // a flat frame has no range to stretch, so it is left as it is rather
+    // than turned black
+    if max <= min {
+        return (0, 255);
+    }
+    (min, max)
+}
+
+/// Walks the levels in the given order until more than `count` pixels were passed
+fn first_past(histogram: &[usize; 256], levels: impl Iterator<Item = usize>, count: usize) -> u8 {
+    let mut passed = 0;
+    for level in levels {
+        passed += histogram[level];
+        if passed > count {
+            return level as u8;
+        }
+    }
+    0
+}
+
+/// Converts the image to luma with the given weights, leaving images that
+/// already are as they are
+pub fn luma(image: &DynamicImage, weights: LumaWeights) -> GrayImage {
+    if let DynamicImage::ImageLuma8(gray) = image {
+        return gray.clone();
+    }
+
+    let [r, g, b] = weights.weights();
+    let rgb = image.to_rgb8();
+    GrayImage::from_fn(rgb.width(), rgb.height(), |x, y| {
+        let pixel = rgb.get_pixel(x, y);
+        let value = pixel[0] as f32 * r + pixel[1] as f32 * g + pixel[2] as f32 * b;
+        image::Luma([value.round().min(255.0) as u8])
+    })
+}
+
+/// Counts how many pixels of the image fall on each luma level
+pub fn luma_histogram(image: &DynamicImage) -> [u64; 256] {
+    let mut histogram = [0; 256];
+    for pixel in image.to_luma8().pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    histogram
+}
+
+/// The normalized values are taken as this many times the radiance of mid-gray
+/// when tone mapping, so that white is where the curves compress the most
+const TONE_MAP_WHITE: f32 = 11.2;
+
+/// Compresses a normalized value with the tone-mapping curve, keeping white at 1
+fn tone_map(value: f32, curve: ToneMap) -> f32 {
+    let radiance = value * TONE_MAP_WHITE;
+    match curve {
+        ToneMap::Off => value,
+        // the extended operator, whose white point maps to exactly 1
+        ToneMap::Reinhard => {
+            radiance * (1.0 + radiance / (TONE_MAP_WHITE * TONE_MAP_WHITE)) / (1.0 + radiance)
+        }
+        ToneMap::Hable => hable(radiance) / hable(TONE_MAP_WHITE),
+    }
+}
+
+fn hable(x: f32) -> f32 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+}
+
+/// Applies the tone map, brightness, contrast, gamma and invert settings to a normalized value
+pub fn adjust(value: f32, settings: &Settings) -> f32 {
+    let value = tone_map(value, settings.tone_map);
+    let value = (value - 0.5) * settings.contrast + 0.5 + settings.brightness;
+    let value = value.clamp(0.0, 1.0).powf(1.0 / settings.gamma);
+    if settings.invert {
+        1.0 - value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_clip_both_ends() {
+        let pixels: Vec<u8> = (0..100).collect();
+        assert_eq!(percentiles(&pixels, 0.0), (0, 99));
+        assert_eq!(percentiles(&pixels, 5.0), (5, 94));
+    }
+
+    #[test]
+    fn a_flat_frame_is_not_stretched() {
+        assert_eq!(percentiles(&[128; 64], 1.0), (0, 255));
+        assert_eq!(percentiles(&[], 1.0), (0, 255));
+
+        let settings = Settings {
+            exposure: Exposure::Normalize,
+            ..Settings::default()
+        };
+        let dark = normalize(&GrayImage::from_pixel(4, 4, image::Luma([64])), &settings);
+        let light = normalize(&GrayImage::from_pixel(4, 4, image::Luma([192])), &settings);
+        assert!(dark.pixels().all(|p| p == dark.get_pixel(0, 0)));
+        assert!(dark.get_pixel(0, 0)[0] < light.get_pixel(0, 0)[0]);
+    }
+
+    #[test]
+    fn hysteresis_holds_a_level_near_its_boundary() {
+        let mut hysteresis = Hysteresis::default();
+        hysteresis.prepare(2, 1, 10, &[3.0, 3.0]);
+
+        // back and forth around the boundary between 3 and 4
+        for value in [3.55, 3.45, 3.6, 3.4, 3.55] {
+            assert_eq!(hysteresis.level(0, value), 3);
+        }
+        assert_eq!(hysteresis.level(0, 3.7), 4);
+        assert_eq!(hysteresis.level(0, 3.4), 4);
+
+        assert_eq!(hysteresis.level(1, 8.0), 8);
+        assert_eq!(hysteresis.level(1, 0.2), 0);
+    }
+
+    #[test]
+    fn hysteresis_starts_over_when_the_view_changes() {
+        let mut hysteresis = Hysteresis::default();
+        hysteresis.prepare(1, 1, 10, &[3.0]);
+        hysteresis.prepare(2, 1, 10, &[5.0, 5.0]);
+        assert_eq!(hysteresis.level(1, 5.5), 5);
+        hysteresis.prepare(2, 1, 4, &[1.0, 1.0]);
+        assert_eq!(hysteresis.level(0, 1.5), 1);
+    }
+}
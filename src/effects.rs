@@ -0,0 +1,214 @@
+use image::{DynamicImage, GenericImageView, ImageBuffer, Luma};
+use std::{
+    f32::consts::PI,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Brightness of the rows between the scanlines of the CRT effect
+const SCANLINE_DIM: f32 = 0.6;
+
+/// Brightness lost in the corners by the CRT effect, as the tube curves away
+const CRT_FALLOFF: f32 = 0.35;
+
+/// Creative filter applied to the frame before it is dithered
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    Off,
+    /// The left half reflected onto the right one
+    Symmetry,
+    /// A wedge of the image repeated around the center, mirrored every other
+    /// time, this many times
+    Kaleidoscope(u32),
+    /// The difference along the diagonal around mid-gray, as if the image
+    /// were engraved and lit from the top left
+    Emboss,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 6] = [
+        Effect::Off,
+        Effect::Symmetry,
+        Effect::Kaleidoscope(4),
+        Effect::Kaleidoscope(6),
+        Effect::Kaleidoscope(8),
+        Effect::Emboss,
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            Effect::Off => String::from("off"),
+            Effect::Symmetry => String::from("symmetry"),
+            Effect::Kaleidoscope(folds) => format!("kaleidoscope {}", folds),
+            Effect::Emboss => String::from("emboss"),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Effect> {
+        Effect::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    pub fn apply(self, image: DynamicImage) -> DynamicImage {
+        match self {
+            Effect::Off => image,
+            Effect::Symmetry => symmetry(&image),
+            Effect::Kaleidoscope(folds) => kaleidoscope(&image, folds),
+            Effect::Emboss => emboss(&image),
+        }
+    }
+}
+
+fn symmetry(image: &DynamicImage) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let rgb = image.to_rgb8();
+
+    DynamicImage::from(ImageBuffer::from_fn(width, height, |x, y| {
+        let x = if x < width / 2 { x } else { width - 1 - x };
+        *rgb.get_pixel(x, y)
+    }))
+}
+
+fn kaleidoscope(image: &DynamicImage, folds: u32) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let rgb = image.to_rgb8();
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+    let wedge = 2.0 * PI / folds.max(1) as f32;
+
+    DynamicImage::from(ImageBuffer::from_fn(width, height, |x, y| {
+        let dx = x as f32 - center.0;
+        let dy = y as f32 - center.1;
+        let radius = dx.hypot(dy);
+
+        // fold the angle into the first half wedge, reflecting every other half
+        let angle = dy.atan2(dx).rem_euclid(wedge);
+        let angle = if angle > wedge / 2.0 {
+            wedge - angle
+        } else {
+            angle
+        };
+
+        let sx = (center.0 + radius * angle.cos()).clamp(0.0, (width - 1) as f32);
+        let sy = (center.1 + radius * angle.sin()).clamp(0.0, (height - 1) as f32);
+        *rgb.get_pixel(sx as u32, sy as u32)
+    }))
+}
+
+fn emboss(image: &DynamicImage) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let luma = image.to_luma8();
+
+    DynamicImage::from(ImageBuffer::from_fn(width, height, |x, y| {
+        // the neighbours along the diagonal, clamped at the borders
+        let lit = luma.get_pixel(x.saturating_sub(1), y.saturating_sub(1))[0];
+        let shaded = luma.get_pixel((x + 1).min(width - 1), (y + 1).min(height - 1))[0];
+        let relief = 128 + (lit as i32 - shaded as i32);
+        Luma([relief.clamp(0, 255) as u8])
+    }))
+}
+
+/// Brightness factor of a cell of the output for the CRT effect: every other
+/// row is dimmed like the gaps between scanlines, and the light falls off
+/// towards the edges of the curved screen
+pub fn crt(x: u32, y: u32, width: u32, height: u32) -> f32 {
+    let dx = (x as f32 + 0.5) / width.max(1) as f32 * 2.0 - 1.0;
+    let dy = (y as f32 + 0.5) / height.max(1) as f32 * 2.0 - 1.0;
+    let curve = 1.0 - CRT_FALLOFF * (dx * dx + dy * dy) / 2.0;
+
+    if y % 2 == 1 {
+        curve * SCANLINE_DIM
+    } else {
+        curve
+    }
+}
+
+/// Corrupts the frame like a damaged video signal: bands of rows slip sideways,
+/// the red and blue channels drift apart and blocks are copied where they do
+/// not belong. The intensity, in the range [0, 1], scales all three, and the
+/// damage is different on every frame
+pub fn glitch(image: &DynamicImage, intensity: f32) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let source = image.to_rgb8();
+    let mut rng = XorShift::from_clock();
+
+    // bands of rows shifted by a random amount
+    let mut shifts = vec![0i64; height as usize];
+    let mut y = 0;
+    while y < height as usize {
+        if rng.unit() < intensity * 0.1 {
+            let band = 1 + rng.below(height / 16 + 1) as usize;
+            let reach = (intensity * width as f32 / 4.0) as u32 + 1;
+            let shift = rng.below(reach * 2) as i64 - reach as i64;
+            for row in shifts.iter_mut().skip(y).take(band) {
+                *row = shift;
+            }
+            y += band;
+        } else {
+            y += 1;
+        }
+    }
+
+    // red to the right and blue to the left
+    let offset = (intensity * width as f32 / 50.0) as i64;
+    let sample = |x: i64, y: u32, channel: usize| {
+        let x = (x - shifts[y as usize]).rem_euclid(width as i64) as u32;
+        source.get_pixel(x, y)[channel]
+    };
+    let mut output = ImageBuffer::from_fn(width, height, |x, y| {
+        let x = x as i64;
+        image::Rgb([
+            sample(x - offset, y, 0),
+            sample(x, y, 1),
+            sample(x + offset, y, 2),
+        ])
+    });
+
+    // blocks copied from elsewhere in the frame
+    let blocks = (intensity * 12.0) as u32;
+    for _ in 0..blocks {
+        let size = (1 + rng.below(width / 8 + 1), 1 + rng.below(height / 16 + 1));
+        let from = (rng.below(width), rng.below(height));
+        let to = (rng.below(width), rng.below(height));
+        for dy in 0..size.1 {
+            for dx in 0..size.0 {
+                let (fx, fy) = (from.0 + dx, from.1 + dy);
+                let (tx, ty) = (to.0 + dx, to.1 + dy);
+                if fx < width && fy < height && tx < width && ty < height {
+                    let pixel = *output.get_pixel(fx, fy);
+                    output.put_pixel(tx, ty, pixel);
+                }
+            }
+        }
+    }
+
+    DynamicImage::from(output)
+}
+
+/// A small pseudo-random generator, plenty for visual noise
+struct XorShift(u64);
+
+impl XorShift {
+    fn from_clock() -> XorShift {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        // the state must never be zero
+        XorShift(nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in the range [0, 1)
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A number in the range [0, n), or 0 when n is 0
+    fn below(&mut self, n: u32) -> u32 {
+        (self.next() % n.max(1) as u64) as u32
+    }
+}
@@ -0,0 +1,49 @@
+use image::DynamicImage;
+
+/// Mean luma the gain aims for by default, as a fraction of full white
+pub const DEFAULT_TARGET: f32 = 0.45;
+
+/// How much of the way to the ideal gain is covered each frame, slow enough
+/// that a passing bright object does not make the whole image pump
+const RATE: f32 = 0.1;
+
+const MIN_GAIN: f32 = 0.25;
+const MAX_GAIN: f32 = 8.0;
+
+/// Software auto-exposure, a digital gain adjusted over time to keep the mean luma near a target
+#[derive(Clone)]
+pub struct AutoExposure {
+    pub target: f32,
+    gain: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> AutoExposure {
+        AutoExposure {
+            target: DEFAULT_TARGET,
+            gain: 1.0,
+        }
+    }
+}
+
+impl AutoExposure {
+    /// Moves the gain towards the one that brings this frame to the target and applies it
+    pub fn apply(&mut self, image: &DynamicImage) -> DynamicImage {
+        let mut gray = image.to_luma8();
+
+        let count = gray.pixels().len().max(1);
+        let mean = gray.pixels().map(|p| p[0] as f32).sum::<f32>() / count as f32 / 255.0;
+        // a black frame, e.g. a covered lens, would send the gain to the maximum
+        if mean > 0.01 {
+            let ideal = (self.target / mean).clamp(MIN_GAIN, MAX_GAIN);
+            self.gain += (ideal - self.gain) * RATE;
+        }
+
+        let gain = self.gain;
+        for pixel in gray.pixels_mut() {
+            pixel[0] = (pixel[0] as f32 * gain).min(255.0) as u8;
+        }
+
+        DynamicImage::ImageLuma8(gray)
+    }
+}
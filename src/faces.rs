@@ -0,0 +1,66 @@
+use image::DynamicImage;
+use rustface::{Detector, ImageData};
+use std::path::Path;
+
+/// Frames are downscaled to this width before looking for faces, which keeps
+/// detection fast enough to run on every frame
+const DETECTION_WIDTH: u32 = 320;
+
+/// A detected face, as fractions of the image size
+#[derive(Clone, Copy, Debug)]
+pub struct Face {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Finds faces with a SeetaFace model
+pub struct FaceDetector {
+    detector: Box<dyn Detector>,
+    /// Faces found in the last rendered frame
+    pub found: Vec<Face>,
+}
+
+impl FaceDetector {
+    pub fn load(model: &Path) -> Result<FaceDetector, String> {
+        let mut detector = rustface::create_detector(&model.to_string_lossy())
+            .map_err(|e| format!("{}: {}", model.display(), e))?;
+        detector.set_min_face_size(20);
+        detector.set_score_thresh(2.0);
+        detector.set_pyramid_scale_factor(0.8);
+        detector.set_slide_window_step(4, 4);
+
+        Ok(FaceDetector {
+            detector,
+            found: Vec::new(),
+        })
+    }
+
+    pub fn detect(&mut self, image: &DynamicImage) {
+        let image = if image.width() > DETECTION_WIDTH {
+            image.thumbnail(DETECTION_WIDTH, u32::MAX)
+        } else {
+            image.clone()
+        };
+        let gray = image.to_luma8();
+        let (width, height) = gray.dimensions();
+
+        let faces = self
+            .detector
+            .detect(&ImageData::new(gray.as_raw(), width, height));
+
+        self.found = faces
+            .iter()
+            .map(|face| {
+                let bbox = face.bbox();
+                Face {
+                    x: bbox.x().max(0) as f32 / width as f32,
+                    y: bbox.y().max(0) as f32 / height as f32,
+                    width: bbox.width() as f32 / width as f32,
+                    height: bbox.height() as f32 / height as f32,
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::app::StatefulList;
+
+/// The snapshots saved in the output directory, newest first
+pub struct Gallery {
+    pub list: StatefulList<PathBuf>,
+    /// Contents of the highlighted snapshot
+    pub preview: String,
+    /// New file name being typed, `None` when not renaming
+    pub renaming: Option<String>,
+}
+
+impl Gallery {
+    pub fn open(dir: &Path) -> io::Result<Gallery> {
+        let mut files: Vec<(SystemTime, PathBuf)> = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "txt") && path.is_file() {
+                let modified = entry
+                    .metadata()?
+                    .modified()
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((modified, path));
+            }
+        }
+        files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+        let mut gallery = Gallery {
+            list: StatefulList::with_items(files.into_iter().map(|(_, path)| path).collect()),
+            preview: String::new(),
+            renaming: None,
+        };
+        if !gallery.list.items.is_empty() {
+            gallery.list.select_first();
+        }
+        gallery.load_preview();
+
+        Ok(gallery)
+    }
+
+    pub fn selected(&self) -> Option<&PathBuf> {
+        self.list.selected().and_then(|i| self.list.items.get(i))
+    }
+
+    pub fn next(&mut self) {
+        self.list.next();
+        self.load_preview();
+    }
+
+    pub fn previous(&mut self) {
+        self.list.previous();
+        self.load_preview();
+    }
+
+    fn load_preview(&mut self) {
+        self.preview = match self.selected() {
+            Some(path) => fs::read_to_string(path).unwrap_or_default(),
+            None => String::new(),
+        };
+    }
+
+    pub fn delete_selected(&mut self) -> io::Result<()> {
+        let i = match self.list.selected() {
+            Some(i) if i < self.list.items.len() => i,
+            _ => return Ok(()),
+        };
+
+        fs::remove_file(&self.list.items[i])?;
+        self.list.items.remove(i);
+
+        let selected = match self.list.items.len() {
+            0 => None,
+            len => Some(i.min(len - 1)),
+        };
+        self.list.state.select(selected);
+        self.load_preview();
+
+        Ok(())
+    }
+
+    /// Renames the highlighted snapshot within its directory, keeping it a text file
+    pub fn rename_selected(&mut self, name: &str) -> io::Result<()> {
+        let i = match self.list.selected() {
+            Some(i) if i < self.list.items.len() => i,
+            _ => return Ok(()),
+        };
+
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Not a file name: {}", name),
+            ));
+        }
+
+        let mut path = self.list.items[i].with_file_name(name);
+        if path.extension().is_none() {
+            path.set_extension("txt");
+        }
+        if path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+
+        fs::rename(&self.list.items[i], &path)?;
+        self.list.items[i] = path;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use image::{DynamicImage, Rgb};
+use tui::{layout::Rect, style::Color};
+
+use crate::canvas::{Canvas, Cell};
+use crate::dither::adjust;
+use crate::effects::crt;
+use crate::resample;
+use crate::settings::Settings;
+
+/// Draws the image in full color, two pixels to a cell: the upper half block
+/// in the color of the top one, over a background in the color of the bottom one
+pub fn render(image: &DynamicImage, view: Rect, settings: &Settings) -> Canvas {
+    let (width, height) = (view.width as u32, view.height as u32 * 2);
+    let pixels = resample::resize(image, width, height, settings.filter).to_rgb8();
+
+    let color = |x: u32, y: u32| {
+        let Rgb(rgb) = *pixels.get_pixel(x, y);
+        let shade = if settings.crt {
+            crt(x, y, width, height)
+        } else {
+            1.0
+        };
+        let [r, g, b] = rgb.map(|c| (adjust(c as f32 / 255.0, settings) * shade * 255.0) as u8);
+        Color::Rgb(r, g, b)
+    };
+
+    let mut canvas = Canvas::new(view.width as usize, view.height as usize);
+    for y in 0..view.height as u32 {
+        for x in 0..width {
+            let cell = Cell {
+                ch: '▀',
+                fg: Some(color(x, y * 2)),
+                bg: Some(color(x, y * 2 + 1)),
+            };
+            canvas.set(x as usize, y as usize, cell);
+        }
+    }
+    canvas
+}
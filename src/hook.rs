@@ -0,0 +1,69 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+/// A shell command run whenever a file is saved, with the path of the file
+/// as its argument, for post-processing such as a notification or an upload.
+/// It runs in the background with its output discarded, since it would draw
+/// over the viewer.
+pub struct Hook {
+    command: String,
+    error_sender: Sender<String>,
+    /// Why the command failed, to be shown to the user
+    pub errors: Receiver<String>,
+}
+
+impl Hook {
+    pub fn new(command: &str) -> Hook {
+        let (error_sender, errors) = mpsc::channel();
+        Hook {
+            command: command.to_string(),
+            error_sender,
+            errors,
+        }
+    }
+
+    pub fn run(&self, path: &Path) {
+        // the path comes after the command, as if typed at the end of it
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.command).arg(path);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(format!("{} \"$@\"", self.command))
+                .arg("sh")
+                .arg(path);
+            command
+        };
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        // started right away, so that it still runs when the viewer is quitting,
+        // and only waited for in the background
+        let name = self.command.clone();
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = self.error_sender.send(format!("{}: {}", name, e));
+                return;
+            }
+        };
+        let errors = self.error_sender.clone();
+        thread::spawn(move || {
+            let error = match child.wait() {
+                Ok(status) if status.success() => return,
+                Ok(status) => format!("{} exited with {}", name, status),
+                Err(e) => format!("{}: {}", name, e),
+            };
+            let _ = errors.send(error);
+        });
+    }
+}
@@ -0,0 +1,335 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::config::Config;
+
+/// Something a key does in the viewer, named in the `[keys]` section of the
+/// config to bind it to another key
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Quit,
+    Help,
+    /// Opens the `:` command line
+    CommandLine,
+    /// Shows the settings panel, the adjust mode of the vim-style scheme
+    AdjustMode,
+    Pause,
+    /// Grabs a single frame while paused, or shows the next one of an animation
+    Step,
+    Slower,
+    Faster,
+    /// Marks the start of the loop of an animation, then its end, then clears it
+    Loop,
+    Fullscreen,
+    EditCharset,
+    FlipAb,
+    Snapshot,
+    Burst,
+    SelfTimer,
+    SelfTimerLength,
+    Reset,
+    Record,
+    Grid,
+    Markers,
+    Faces,
+    Backdrop,
+    ClearMasks,
+    AutoPause,
+    Qr,
+    FewerPixels,
+    MorePixels,
+    Deinterlace,
+    Microphone,
+    Reactive,
+    Stabilize,
+    Prompter,
+    Split,
+    Retro,
+    Ordered,
+    Braille,
+    NextRamp,
+    Edges,
+    Color,
+    Clock,
+    Histogram,
+    Settings,
+}
+
+impl Action {
+    pub const ALL: [Action; 42] = [
+        Action::Quit,
+        Action::Help,
+        Action::CommandLine,
+        Action::AdjustMode,
+        Action::Pause,
+        Action::Step,
+        Action::Slower,
+        Action::Faster,
+        Action::Loop,
+        Action::Fullscreen,
+        Action::EditCharset,
+        Action::FlipAb,
+        Action::Snapshot,
+        Action::Burst,
+        Action::SelfTimer,
+        Action::SelfTimerLength,
+        Action::Reset,
+        Action::Record,
+        Action::Grid,
+        Action::Markers,
+        Action::Faces,
+        Action::Backdrop,
+        Action::ClearMasks,
+        Action::AutoPause,
+        Action::Qr,
+        Action::FewerPixels,
+        Action::MorePixels,
+        Action::Deinterlace,
+        Action::Microphone,
+        Action::Reactive,
+        Action::Stabilize,
+        Action::Prompter,
+        Action::Split,
+        Action::Retro,
+        Action::Ordered,
+        Action::Braille,
+        Action::NextRamp,
+        Action::Edges,
+        Action::Color,
+        Action::Clock,
+        Action::Histogram,
+        Action::Settings,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::CommandLine => "command",
+            Action::AdjustMode => "adjust",
+            Action::Pause => "pause",
+            Action::Step => "step",
+            Action::Slower => "slower",
+            Action::Faster => "faster",
+            Action::Loop => "loop",
+            Action::Fullscreen => "fullscreen",
+            Action::EditCharset => "edit_charset",
+            Action::FlipAb => "flip_ab",
+            Action::Snapshot => "snapshot",
+            Action::Burst => "burst",
+            Action::SelfTimer => "self_timer",
+            Action::SelfTimerLength => "self_timer_length",
+            Action::Reset => "reset",
+            Action::Record => "record",
+            Action::Grid => "grid",
+            Action::Markers => "markers",
+            Action::Faces => "faces",
+            Action::Backdrop => "backdrop",
+            Action::ClearMasks => "clear_masks",
+            Action::AutoPause => "auto_pause",
+            Action::Qr => "qr",
+            Action::FewerPixels => "fewer_pixels",
+            Action::MorePixels => "more_pixels",
+            Action::Deinterlace => "deinterlace",
+            Action::Microphone => "microphone",
+            Action::Reactive => "reactive",
+            Action::Stabilize => "stabilize",
+            Action::Prompter => "prompter",
+            Action::Split => "split",
+            Action::Retro => "retro",
+            Action::Ordered => "ordered",
+            Action::Braille => "braille",
+            Action::NextRamp => "ramp",
+            Action::Edges => "edges",
+            Action::Color => "color",
+            Action::Clock => "clock",
+            Action::Histogram => "histogram",
+            Action::Settings => "settings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+/// The keys of the viewer's actions. The arrows and hjkl, Esc, Enter and the
+/// preset digits depend on what is open, so they are not part of it.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let bindings = [
+            ('q', Action::Quit),
+            ('?', Action::Help),
+            (' ', Action::Pause),
+            ('.', Action::Step),
+            ('-', Action::Slower),
+            ('+', Action::Faster),
+            ('=', Action::Faster),
+            ('L', Action::Loop),
+            ('f', Action::Fullscreen),
+            ('e', Action::EditCharset),
+            ('b', Action::FlipAb),
+            ('s', Action::Snapshot),
+            ('S', Action::Burst),
+            ('t', Action::SelfTimer),
+            ('T', Action::SelfTimerLength),
+            ('r', Action::Reset),
+            ('R', Action::Record),
+            ('g', Action::Grid),
+            ('x', Action::Markers),
+            ('D', Action::Faces),
+            ('V', Action::Backdrop),
+            ('M', Action::ClearMasks),
+            ('A', Action::AutoPause),
+            ('Q', Action::Qr),
+            ('[', Action::FewerPixels),
+            (']', Action::MorePixels),
+            ('I', Action::Deinterlace),
+            ('m', Action::Microphone),
+            ('W', Action::Reactive),
+            ('Z', Action::Stabilize),
+            ('p', Action::Prompter),
+            ('B', Action::Split),
+            ('G', Action::Retro),
+            ('O', Action::Ordered),
+            ('U', Action::Braille),
+            ('C', Action::NextRamp),
+            ('E', Action::Edges),
+            ('K', Action::Color),
+            ('c', Action::Clock),
+            ('H', Action::Histogram),
+        ];
+
+        let mut bindings: Vec<(KeyCode, Action)> = bindings
+            .into_iter()
+            .map(|(c, action)| (KeyCode::Char(c), action))
+            .collect();
+        bindings.push((KeyCode::Tab, Action::Settings));
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default keys with the `:` command line and the adjust mode of the
+    /// vim-style scheme on top
+    pub fn vim() -> Keymap {
+        let mut keymap = Keymap::default();
+        keymap.bind(KeyCode::Char(':'), Action::CommandLine);
+        keymap.bind(KeyCode::Char('i'), Action::AdjustMode);
+        keymap
+    }
+
+    /// Starts from the scheme chosen by `keys.vim` and binds the actions named
+    /// in the `[keys]` section to their keys, e.g. `snapshot = "x"`
+    pub fn from_config(config: &Config) -> Result<Keymap, String> {
+        let mut keymap = match config.get_bool("keys.vim") {
+            Some(true) => Keymap::vim(),
+            _ => Keymap::default(),
+        };
+
+        for (name, key) in config.section("keys") {
+            if name == "vim" {
+                continue;
+            }
+            let action =
+                Action::from_name(name).ok_or_else(|| format!("Unknown action: {}", name))?;
+            let key = parse_key(key).ok_or_else(|| format!("Not a key: {}", key))?;
+            keymap.bind(key, action);
+        }
+
+        Ok(keymap)
+    }
+
+    /// Moves the action to the key, taking the key away from any other action
+    pub fn bind(&mut self, key: KeyCode, action: Action) {
+        self.bindings.retain(|(k, a)| *k != key && *a != action);
+        self.bindings.push((key, action));
+    }
+
+    /// The action of a key, if any; keys held with Alt or Ctrl have none, as
+    /// Alt and the digits save presets
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
+        {
+            return None;
+        }
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == key.code)
+            .map(|(_, action)| *action)
+    }
+}
+
+/// Reads a key of the config: a single character, `space` or `tab`
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_control() => Some(KeyCode::Char(c)),
+        _ => match name {
+            "space" => Some(KeyCode::Char(' ')),
+            "tab" => Some(KeyCode::Tab),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn every_action_has_a_name_of_its_own() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::from_name("nothing"), None);
+    }
+
+    #[test]
+    fn keys_held_with_alt_or_ctrl_do_nothing() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(press('s')), Some(Action::Snapshot));
+        let key = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT);
+        assert_eq!(keymap.action(key), None);
+    }
+
+    #[test]
+    fn the_vim_scheme_adds_its_keys() {
+        assert_eq!(Keymap::default().action(press(':')), None);
+        assert_eq!(Keymap::vim().action(press(':')), Some(Action::CommandLine));
+        assert_eq!(Keymap::vim().action(press('i')), Some(Action::AdjustMode));
+    }
+
+    #[test]
+    fn binding_moves_the_action_and_takes_the_key() {
+        let mut keymap = Keymap::default();
+        keymap.bind(KeyCode::Char(' '), Action::Snapshot);
+        assert_eq!(keymap.action(press(' ')), Some(Action::Snapshot));
+        assert_eq!(keymap.action(press('s')), None);
+    }
+
+    #[test]
+    fn reads_the_keys_of_the_config() {
+        let config =
+            Config::parse("[keys]\nvim = true\nsnapshot = \"space\"\npause = \"P\"").unwrap();
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(keymap.action(press(' ')), Some(Action::Snapshot));
+        assert_eq!(keymap.action(press('P')), Some(Action::Pause));
+        assert_eq!(keymap.action(press(':')), Some(Action::CommandLine));
+
+        let config = Config::parse("[keys]\nteleport = \"x\"").unwrap();
+        assert!(Keymap::from_config(&config).is_err());
+        let config = Config::parse("[keys]\nsnapshot = \"ctrl\"").unwrap();
+        assert!(Keymap::from_config(&config).is_err());
+    }
+}
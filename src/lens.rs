@@ -0,0 +1,63 @@
+use image::{ImageBuffer, Rgb};
+
+use crate::source::RgbFrame;
+
+/// Straightens the lines a wide-angle lens bends, with the radial model
+/// `r' = r (1 + k1 r^2 + k2 r^4)` where `r` is the distance from the center
+/// relative to half the frame diagonal
+pub struct Lens {
+    pub k1: f32,
+    pub k2: f32,
+    /// Source pixel of every output pixel, `None` where it falls outside the
+    /// frame, computed once per frame size
+    map: Vec<Option<(u32, u32)>>,
+    size: (u32, u32),
+}
+
+impl Lens {
+    pub fn new(k1: f32, k2: f32) -> Lens {
+        Lens {
+            k1,
+            k2,
+            map: Vec::new(),
+            size: (0, 0),
+        }
+    }
+
+    /// Negative coefficients correct barrel distortion, positive ones pincushion
+    pub fn correct(&mut self, frame: &RgbFrame) -> RgbFrame {
+        let size = frame.dimensions();
+        if self.size != size {
+            self.map = self.build_map(size);
+            self.size = size;
+        }
+
+        ImageBuffer::from_fn(size.0, size.1, |x, y| {
+            match self.map[(y * size.0 + x) as usize] {
+                Some((sx, sy)) => *frame.get_pixel(sx, sy),
+                None => Rgb([0, 0, 0]),
+            }
+        })
+    }
+
+    fn build_map(&self, (width, height): (u32, u32)) -> Vec<Option<(u32, u32)>> {
+        let center = (width as f32 / 2.0, height as f32 / 2.0);
+        let radius = center.0.hypot(center.1).max(1.0);
+
+        let mut map = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let dx = (x as f32 + 0.5 - center.0) / radius;
+                let dy = (y as f32 + 0.5 - center.1) / radius;
+                let r2 = dx * dx + dy * dy;
+                let scale = 1.0 + self.k1 * r2 + self.k2 * r2 * r2;
+
+                let sx = center.0 + dx * scale * radius;
+                let sy = center.1 + dy * scale * radius;
+                let inside = sx >= 0.0 && sy >= 0.0 && sx < width as f32 && sy < height as f32;
+                map.push(inside.then_some((sx as u32, sy as u32)));
+            }
+        }
+        map
+    }
+}
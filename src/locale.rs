@@ -0,0 +1,20 @@
+use std::env;
+
+/// Whether the terminal can be expected to draw glyphs beyond ASCII, such as
+/// the shade and half blocks. Terminals take the encoding from the locale, so
+/// the first of the locale variables that is set decides. Windows terminals
+/// draw them regardless.
+pub fn unicode_supported() -> bool {
+    if cfg!(windows) {
+        return true;
+    }
+
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
@@ -0,0 +1,59 @@
+use crate::canvas::Cell;
+use crate::overlay::Corner;
+use image::{imageops::FilterType, GenericImageView, Pixel};
+use std::path::Path;
+use tui::style::Color;
+
+/// Glyphs the logo is drawn with, from the darkest to the brightest pixels
+const RAMP: &[char] = &['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// A small image drawn over every frame, for branding recordings and streams
+pub struct Logo {
+    pub width: usize,
+    pub height: usize,
+    /// `None` where the image is transparent and the frame shows through
+    cells: Vec<Option<Cell>>,
+    pub corner: Corner,
+}
+
+impl Logo {
+    /// Converts an image to `width` columns of glyphs, where pixels that are
+    /// transparent or darker than `threshold` let the frame show through
+    pub fn load(path: &Path, width: u32, threshold: u8, corner: Corner) -> Result<Logo, String> {
+        let image = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        // glyphs are about twice as tall as they are wide
+        let width = width.max(1);
+        let (image_width, image_height) = image.dimensions();
+        let height = (width * image_height / image_width.max(1) / 2).max(1);
+        let image = image.resize_exact(width, height, FilterType::Triangle);
+
+        let cells = image
+            .pixels()
+            .map(|(_, _, pixel)| {
+                let luma = pixel.to_luma()[0];
+                if pixel[3] < 128 || luma < threshold {
+                    return None;
+                }
+
+                let ch = RAMP[luma as usize * RAMP.len() / 256];
+                Some(Cell {
+                    ch,
+                    fg: Some(Color::Rgb(pixel[0], pixel[1], pixel[2])),
+                    bg: None,
+                })
+            })
+            .collect();
+
+        Ok(Logo {
+            width: width as usize,
+            height: height as usize,
+            cells,
+            corner,
+        })
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        self.cells[y * self.width + x]
+    }
+}
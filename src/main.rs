@@ -1,121 +1,336 @@
+mod ansi;
+mod app;
+mod asciivid;
+mod audio;
+mod backdrop;
+mod bayer;
+mod blue_noise;
+mod braille;
+mod canvas;
+mod clahe;
+mod clipboard;
+mod color;
+mod command;
+mod config;
+mod deinterlace;
+mod dialog;
+mod dither;
+mod effects;
+mod exposure;
+mod faces;
+mod gallery;
+mod halfblock;
+mod hook;
+mod keymap;
+mod lens;
+mod locale;
+mod logo;
+mod motion;
+mod mqtt;
+mod overlay;
+mod playlist;
+mod presets;
+mod preview;
+mod privacy;
+mod prompter;
+mod qr;
+mod quantize;
+mod raster;
+mod recorder;
+mod replay;
+mod resample;
+mod retro;
+mod settings;
+mod snapshot;
+mod source;
+mod stabilizer;
+mod theme;
+mod timer;
+mod toast;
+mod ui;
+mod viewport;
+mod webhook;
+mod yuv;
+
+use app::App;
+use bayer::BayerPattern;
+use command::Command;
+use config::Config;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use image::{DynamicImage, GenericImageView, ImageBuffer, Rgb};
-use nokhwa::{
-    pixel_format::RgbFormat,
-    utils::{CameraIndex, RequestedFormat, RequestedFormatType},
-    Camera,
-};
+use dialog::{Dialog, DialogKind};
+use hook::Hook;
+use keymap::{Action, Keymap};
+use lens::Lens;
+use logo::Logo;
+use mqtt::{Broker, Publisher};
+use overlay::Corner;
+use playlist::{Playlist, DEFAULT_DURATION};
+use privacy::{Mask, MaskMode};
+use qr::QrScanner;
+use recorder::RecordFormat;
+use settings::{on_off, Charset, LumaWeights, Setting};
+use source::Source;
+use stabilizer::Stabilizer;
 use std::{
+    env,
     error::Error,
     io,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
+use theme::Theme;
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::Spans,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    layout::Rect,
+    Terminal,
 };
+use ui::ui;
+use webhook::Webhook;
 
-struct StatefulList<T> {
-    state: ListState,
-    items: Vec<T>,
-}
+/// How far the movement keys pan the viewer, as a fraction of the view
+const PAN_STEP: f32 = 0.1;
+
+/// Seconds the arrow keys seek an animation or a recording by
+const SEEK_STEP: f32 = 5.0;
+
+/// Lines of the help overlay that page up and page down scroll by
+const HELP_PAGE: u16 = 10;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let connect_last = args.iter().any(|arg| arg == "--last");
+    // `play <file>` opens a recording, or any file the viewer can open, right away
+    let play = match args.first().map(String::as_str) {
+        Some("play") => Some(args.get(1).ok_or("Usage: play <file>")?),
+        _ => None,
+    };
+    let config = Config::load()?;
+    let theme = Theme::from_config(&config)?;
+
+    // setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
 
-impl<T> StatefulList<T> {
-    fn with_items(items: Vec<T>) -> StatefulList<T> {
-        StatefulList {
-            state: ListState::default(),
-            items,
+    // create app and run it
+    let mut app = App::new();
+    app.theme = theme;
+    app.vim_keys = config.get_bool("keys.vim") == Some(true);
+    app.keymap = Keymap::from_config(&config)?;
+    if let Some(dir) = config.get("output.dir") {
+        app.output_dir = PathBuf::from(dir);
+    }
+    if let Some(format) = config.get("output.recording") {
+        app.record_format = RecordFormat::from_name(format)
+            .ok_or_else(|| format!("Unknown recording format: {}", format))?;
+    }
+    if let Some(regions) = config.get("privacy.regions") {
+        app.privacy.masks = Mask::parse_list(regions)?;
+    }
+    if let Some(mode) = config.get("privacy.mode") {
+        app.privacy.mode =
+            MaskMode::from_name(mode).ok_or_else(|| format!("Unknown privacy mode: {}", mode))?;
+    }
+    if let Some(art) = config.get("background.art") {
+        app.backdrop_art = Some(PathBuf::from(art));
+    }
+    if let Some(format) = config.get("camera.format") {
+        app.camera_format = Some(
+            format
+                .to_ascii_uppercase()
+                .parse()
+                .map_err(|_| format!("Unknown camera format: {}", format))?,
+        );
+    }
+    if let Some(camera) = config.get("bayer.camera") {
+        let pattern = config.get("bayer.pattern").unwrap_or("rggb");
+        let pattern = BayerPattern::from_name(pattern)
+            .ok_or_else(|| format!("Unknown Bayer pattern: {}", pattern))?;
+        app.bayer = Some((camera.to_string(), pattern));
+    }
+    if config.get("lens.k1").is_some() || config.get("lens.k2").is_some() {
+        let coefficient = |key: &str| match config.get(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Not a lens coefficient: {}", value)),
+            None => Ok(0.0),
+        };
+        app.lens = Some(Lens::new(coefficient("lens.k1")?, coefficient("lens.k2")?));
+    }
+    if let Some(depth) = config.get("reactive.depth") {
+        app.reactive_depth = depth
+            .parse()
+            .map_err(|_| format!("Not a reactive depth: {}", depth))?;
+    }
+    if let Some(target) = config.get("exposure.target") {
+        app.exposure.target = target
+            .parse()
+            .ok()
+            .filter(|t| (0.0..=1.0).contains(t))
+            .ok_or_else(|| format!("Not an exposure target: {}", target))?;
+    }
+    if let Some(pixels) = config.get("display.cell_pixels") {
+        app.cell_pixels = Some(
+            pixels
+                .parse()
+                .ok()
+                .filter(|p| *p > 0)
+                .ok_or_else(|| format!("Not a number of pixels: {}", pixels))?,
+        );
+    }
+    for (name, ramp) in config.section("charsets") {
+        let charset =
+            Charset::parse(name, ramp).map_err(|e| format!("charsets.{}: {}", name, e))?;
+        if charset.looks_reversed() {
+            app.toasts.push(format!(
+                "charsets.{} seems to go from light to dark, ramps go from dark to light",
+                name
+            ));
+        }
+        // a ramp of the same name replaces the built-in one
+        match app.charsets.iter_mut().find(|c| c.name == name) {
+            Some(existing) => *existing = charset,
+            None => app.charsets.push(charset),
         }
     }
-
-    fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
+    if let Some(unicode) = config.get_bool("display.unicode") {
+        app.unicode = unicode;
+    }
+    if let Some(weights) = config.get("luma.weights") {
+        app.settings.luma = LumaWeights::from_name(weights)
+            .ok_or_else(|| format!("Unknown luma weights: {}", weights))?;
+    }
+    if let Some(file) = config.get("prompter.file") {
+        app.prompter_file = Some(PathBuf::from(file));
+    }
+    if let Some(speed) = config.get("prompter.speed") {
+        app.prompter_speed = speed
+            .parse()
+            .map_err(|_| format!("Not a prompter speed: {}", speed))?;
+    }
+    if let Some(path) = config.get("logo.path") {
+        let corner = match config.get("logo.corner") {
+            Some(corner) => Corner::from_name(corner)
+                .ok_or_else(|| format!("Unknown logo corner: {}", corner))?,
+            None => Corner::BottomRight,
+        };
+        let width = match config.get("logo.width") {
+            Some(width) => width
+                .parse()
+                .map_err(|_| format!("Not a logo width: {}", width))?,
+            None => 16,
+        };
+        let threshold = match config.get("logo.threshold") {
+            Some(threshold) => threshold
+                .parse()
+                .map_err(|_| format!("Not a logo threshold: {}", threshold))?,
             None => 0,
         };
-        self.state.select(Some(i));
+        app.logo = Some(Logo::load(Path::new(path), width, threshold, corner)?);
     }
-
-    fn selected(&self) -> Option<usize> {
-        match self.state.selected() {
-            Some(i) => return Some(i),
-            None => return None,
-        };
+    if let Some(address) = config.get("mqtt.broker") {
+        let username = config.get("mqtt.username").map(String::from);
+        let password = config.get("mqtt.password").map(String::from);
+        // MQTT only sends a password along with a username
+        if password.is_some() && username.is_none() {
+            return Err("An MQTT password needs a username".into());
+        }
+        app.mqtt = Some(Publisher::start(Broker {
+            address: address.to_string(),
+            topic: config
+                .get("mqtt.topic")
+                .unwrap_or("ascii-camera/motion")
+                .to_string(),
+            username,
+            password,
+        }));
     }
-
-    fn select_first(&mut self) {
-        self.state.select(Some(0));
+    if let Some(url) = config.get("webhook.motion") {
+        app.motion_webhook = Some(Webhook::start(url)?);
     }
-
-    fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+    if let Some(url) = config.get("webhook.snapshot") {
+        app.snapshot_webhook = Some(Webhook::start(url)?);
+    }
+    if let Some(command) = config.get("hooks.on_snapshot") {
+        app.snapshot_hook = Some(Hook::new(command));
+    }
+    if let Some(command) = config.get("hooks.on_recording") {
+        app.recording_hook = Some(Hook::new(command));
+    }
+    if let Some(model) = config.get("faces.model") {
+        app.face_model = Some(PathBuf::from(model));
+    }
+    if let Some(corner) = config.get("clock.corner") {
+        app.clock_corner =
+            Corner::from_name(corner).ok_or_else(|| format!("Unknown clock corner: {}", corner))?;
+    }
+    if config.get_bool("clock.show") == Some(true) {
+        app.clock = Some(app.clock_corner);
+    }
+    if let Some(secs) = config.get("idle.after_secs") {
+        let secs = secs
+            .parse()
+            .map_err(|_| format!("Not an idle time in seconds: {}", secs))?;
+        app.idle_after = Some(Duration::from_secs(secs));
+    }
+    if let Some(enabled) = config.get_bool("auto_pause.enabled") {
+        app.auto_pause = enabled;
+    }
+    if let Some(secs) = config.get("auto_pause.after_secs") {
+        let secs = secs
+            .parse()
+            .map_err(|_| format!("Not an auto-pause time in seconds: {}", secs))?;
+        app.auto_pause_after = Duration::from_secs(secs);
+    }
+    if let Some(bell) = config.get_bool("feedback.bell") {
+        app.bell = bell;
+    }
+    if let Some(flash) = config.get_bool("feedback.flash") {
+        app.flash = flash;
+    }
+    // a burst of no snapshots would leave 'S' doing nothing
+    if let Some(count) = config.get("burst.count") {
+        app.burst_count = match count.parse() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(format!("Not a burst count: {}", count).into()),
         };
-        self.state.select(Some(i));
     }
-}
-
-struct App {
-    menu: StatefulList<(String, CameraIndex)>,
-    camera: Option<Camera>,
-    paused: bool,
-    last_frame: Option<ImageBuffer<Rgb<u8>, Vec<u8>>>,
-}
-
-impl App {
-    fn new() -> App {
-        let cameras = match nokhwa::query(nokhwa::utils::ApiBackend::Auto) {
-            Ok(cs) => cs,
-            Err(_) => panic!("No camera found"),
+    if let Some(ms) = config.get("burst.interval_ms") {
+        app.burst_interval = match ms.parse() {
+            Ok(ms) if ms > 0 => Duration::from_millis(ms),
+            _ => return Err(format!("Not a burst interval in milliseconds: {}", ms).into()),
         };
-
-        let cameras: Vec<(String, CameraIndex)> = cameras
-            .iter()
-            .map(|c| (c.human_name(), c.index().clone()))
-            .collect();
-
-        App {
-            menu: StatefulList::with_items(cameras),
-            camera: None,
-            paused: false,
-            last_frame: None,
-        }
     }
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    // setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // create app and run it
-    let tick_rate = Duration::from_millis(15);
-    let app = App::new();
-    let res = run_app(&mut terminal, app, tick_rate);
+    if let Some(path) = play {
+        app.open_path(path);
+    } else if let Some(items) = config.get("playlist.items") {
+        let duration = match config.get("playlist.duration_secs") {
+            Some(secs) => Duration::from_secs(
+                secs.parse()
+                    .map_err(|_| format!("Not a playlist duration: {}", secs))?,
+            ),
+            None => DEFAULT_DURATION,
+        };
+        app.playlist = Some(Playlist::parse(items, duration)?);
+        app.play_current();
+    } else if connect_last || config.get_bool("auto_connect") == Some(true) {
+        app.connect_to_last();
+    }
+    let res = run_app(&mut terminal, app);
 
     // restore terminal
     disable_raw_mode()?;
@@ -123,6 +338,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         terminal.backend_mut(),
         LeaveAlternateScreen,
         DisableMouseCapture,
+        DisableBracketedPaste,
     )?;
     terminal.show_cursor()?;
 
@@ -133,61 +349,34 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut app: App,
-    tick_rate: Duration,
-) -> io::Result<()> {
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     let mut last_tick = Instant::now();
-    app.menu.select_first();
     loop {
+        app.poll_opening();
+        app.poll_playlist();
+        app.poll_motion_events();
+        app.poll_hook_errors();
         terminal.draw(|f| ui(f, &mut app))?;
 
+        let tick_rate = app.tick_rate();
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match app.camera.as_mut() {
-                    Some(_) => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Char(' ') => {
-                            app.paused = !app.paused;
-                            app.last_frame = None;
-                        }
-                        KeyCode::Esc => {
-                            app.paused = false;
-                            app.last_frame = None;
-                            app.camera = None;
-                        }
-                        _ => {}
-                    },
-                    None => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Down => app.menu.next(),
-                        KeyCode::Up => app.menu.previous(),
-                        KeyCode::Enter => {
-                            let camera = &app.menu.items[app.menu.selected().unwrap()];
-
-                            match Camera::new(
-                                camera.1.clone(),
-                                RequestedFormat::new::<RgbFormat>(
-                                    RequestedFormatType::AbsoluteHighestFrameRate,
-                                ),
-                            ) {
-                                Ok(cam) => app.camera = Some(cam),
-                                Err(_) => {
-                                    // todo: handle this error better (it's ugly)
-                                    let index = app.menu.selected().unwrap();
-                                    let x = &mut app.menu.items[index];
-                                    x.0.push_str(" - An Error occured");
-                                }
-                            };
-                        }
-
-                        _ => {}
-                    },
+            match event::read()? {
+                Event::Key(key) => {
+                    // any input wakes the viewer up from idling
+                    app.motion.touch();
+                    if handle_key(&mut app, key) {
+                        return Ok(());
+                    }
+                }
+                Event::Mouse(mouse) => {
+                    app.motion.touch();
+                    handle_mouse(&mut app, mouse);
                 }
+                Event::Paste(text) => handle_paste(&mut app, &text),
+                _ => {}
             }
         }
         if last_tick.elapsed() >= tick_rate {
@@ -196,142 +385,554 @@ fn run_app<B: Backend>(
     }
 }
 
-const MENU: &str = r#"
-Controls:
- - 'q' - quit the application
- - 'up' and 'down' arrow to navigate the camera list
- - 'enter' to select a camera
- - 'spacebar' to pause the viewer
- - 'esc' to return to the main menu
-
-Known issues:
- - The framerate decreases when the window size or camera resolution increase 
- - The image is not very stable; lots of blinking and jittering
- - The image ratio is not maintained
- - The only way to scale up or down the viewer is either by resizing the terminal window or zooming
-"#;
-
-fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    let size = f.size();
-
-    match app.camera.as_mut() {
-        None => {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(25), Constraint::Percentage(75)].as_ref())
-                .split(Rect::new(0, 0, size.width / 2, size.height));
-
-            let cameras: Vec<ListItem> = app
-                .menu
-                .items
-                .iter()
-                .map(|i| {
-                    let lines = vec![Spans::from(i.0.clone())];
-                    ListItem::new(lines).style(Style::default().fg(Color::White))
-                })
-                .collect();
-
-            // create a List from all the cameras and highlight the currently selected one
-            let cameras = List::new(cameras)
-                .block(Block::default().borders(Borders::ALL).title("Cameras"))
-                .highlight_style(Style::default().bg(Color::White).fg(Color::Black))
-                .highlight_symbol("> ");
-
-            f.render_stateful_widget(cameras, chunks[0], &mut app.menu.state);
-
-            let instructions = Paragraph::new(MENU)
-                .block(Block::default().borders(Borders::ALL).title("Info"))
-                .wrap(Wrap { trim: true });
-
-            f.render_widget(instructions, chunks[1]);
+/// Applies a key press to the app, returning `true` when the user asked to quit
+fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    if app.dialog.is_some() {
+        return handle_dialog_key(app, key);
+    }
+
+    // only cancelling or quitting makes sense while a camera is being opened
+    if app.opening.is_some() {
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc => app.opening = None,
+            _ => {}
         }
-        Some(camera) => {
-            let mut title = "View";
+        return false;
+    }
 
-            let dithered_text = match app.last_frame.as_mut() {
-                Some(img) => {
-                    title = "View - Paused";
+    if app.charset_editor.is_some() {
+        handle_charset_editor_key(app, key);
+        return false;
+    }
 
-                    // rezise the image
-                    let image = DynamicImage::from(img.clone()).resize_exact(
-                        size.width.into(),
-                        size.height.into(),
-                        image::imageops::FilterType::Nearest,
-                    );
+    if app.command.is_some() {
+        return handle_command_key(app, key);
+    }
 
-                    dither_image(image)
-                }
-                None => {
-                    // get a new frame
-                    let frame = camera.frame().unwrap();
-                    let decoded = frame.decode_image::<RgbFormat>().unwrap();
+    if app.open_prompt.is_some() {
+        handle_open_prompt_key(app, key);
+        return false;
+    }
 
-                    if app.paused {
-                        app.last_frame = Some(decoded.clone());
-                    }
+    // the help overlay swallows every key until it is closed
+    if app.show_help {
+        match key.code {
+            KeyCode::Char('q') => return request_quit(app),
+            KeyCode::Char('?') | KeyCode::Esc => {
+                app.show_help = false;
+                app.help_scroll = 0;
+            }
+            // the overlay stops the scrolling at the end of the controls
+            KeyCode::Up | KeyCode::Char('k') => app.help_scroll = app.help_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => app.help_scroll += 1,
+            KeyCode::PageUp => app.help_scroll = app.help_scroll.saturating_sub(HELP_PAGE),
+            KeyCode::PageDown => app.help_scroll += HELP_PAGE,
+            _ => {}
+        }
+        return false;
+    }
 
-                    // rezise the image
-                    // ! This does not keep aspect ratio
-                    let image = DynamicImage::from(decoded).resize_exact(
-                        size.width.into(),
-                        size.height.into(),
-                        image::imageops::FilterType::Nearest,
-                    );
+    // the viewer's actions go through the keymap, the keys that depend on
+    // what is open are handled below
+    if let (Some(_), Some(action)) = (app.source.as_ref(), app.keymap.action(key)) {
+        return run_action(app, action);
+    }
 
-                    dither_image(image)
+    match app.source.as_mut() {
+        Some(_) => match key.code {
+            KeyCode::Esc if app.vim_keys && app.show_settings => app.show_settings = false,
+            KeyCode::Char(c @ '1'..='9') => {
+                let slot = c as usize - '0' as usize;
+                if key.modifiers.contains(KeyModifiers::ALT) {
+                    app.save_preset(slot);
+                } else {
+                    app.apply_preset(slot);
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') if app.show_settings => app.settings_panel.previous(),
+            KeyCode::Down | KeyCode::Char('j') if app.show_settings => app.settings_panel.next(),
+            KeyCode::Enter if app.show_settings => {
+                let selected = app.settings_panel.selected();
+                if selected.map(|i| app.settings_panel.items[i]) == Some(Setting::Reset) {
+                    app.reset_to_defaults();
                 }
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l')
+                if app.show_settings =>
+            {
+                let step = match key.code {
+                    KeyCode::Left | KeyCode::Char('h') => -1,
+                    _ => 1,
+                };
+                if let Some(i) = app.settings_panel.selected() {
+                    match app.settings_panel.items[i] {
+                        Setting::Charset => app.cycle_charset(step),
+                        setting => app.settings.adjust(setting, step),
+                    }
+                }
+            }
+            KeyCode::Left | KeyCode::Right if matches!(app.source, Some(Source::Art { .. })) => {
+                let offset = if key.code == KeyCode::Left {
+                    -SEEK_STEP
+                } else {
+                    SEEK_STEP
+                };
+                if let Some(animation) = app.source.as_mut().and_then(|s| s.animation_mut()) {
+                    animation.seek(offset);
+                }
+            }
+            KeyCode::Left | KeyCode::Right if app.paused => {
+                let step = if key.code == KeyCode::Left { -1 } else { 1 };
+                if let Some(frame) = app.replay.step(step) {
+                    app.last_frame = Some(frame);
+                }
+            }
+            KeyCode::Char('h') | KeyCode::Left => app.pan(PAN_STEP, 0.0),
+            KeyCode::Char('l') | KeyCode::Right => app.pan(-PAN_STEP, 0.0),
+            KeyCode::Char('k') | KeyCode::Up => app.pan(0.0, PAN_STEP),
+            KeyCode::Char('j') | KeyCode::Down => app.pan(0.0, -PAN_STEP),
+            KeyCode::Esc => {
+                // going back to the menu stops the playlist too
+                app.playlist = None;
+                app.close_camera();
+            }
+            _ => {}
+        },
+        None if app.gallery.is_some() => handle_gallery_key(app, key),
+        None if app.calibration.is_some() => match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Left | KeyCode::Char('h') => app.adjust_calibration(-1),
+            KeyCode::Right | KeyCode::Char('l') => app.adjust_calibration(1),
+            KeyCode::Enter => app.finish_calibration(),
+            KeyCode::Esc => app.calibration = None,
+            _ => {}
+        },
+        None if app.monitor.is_some() => match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc | KeyCode::Char('m') => app.monitor = None,
+            _ => {}
+        },
+        None if app.filtering => match key.code {
+            KeyCode::Char(c) => {
+                app.filter.push(c);
+                app.apply_filter();
+            }
+            KeyCode::Backspace => {
+                app.filter.pop();
+                app.apply_filter();
+            }
+            KeyCode::Esc => {
+                app.filtering = false;
+                app.filter.clear();
+                app.apply_filter();
+            }
+            KeyCode::Down => app.menu.next(),
+            KeyCode::Up => app.menu.previous(),
+            KeyCode::Enter => {
+                app.filtering = false;
+                app.open_selected_camera();
+            }
+            _ => {}
+        },
+        None => match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Char('/') => app.filtering = true,
+            KeyCode::Char('g') => app.open_gallery(),
+            KeyCode::Char('m') => app.open_monitor(),
+            KeyCode::Char('a') => app.open_calibration(),
+            KeyCode::Char('o') => app.open_prompt = Some(String::new()),
+            KeyCode::Char(c @ '1'..='9') => app.open_recent(c as usize - '1' as usize),
+            KeyCode::Char('?') => app.show_help = true,
+            KeyCode::Char('i') => app.show_info = !app.show_info,
+            KeyCode::Down | KeyCode::Char('j') => app.menu.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.menu.previous(),
+            KeyCode::PageDown => app.menu.jump(app.menu_area.height.max(1).into()),
+            KeyCode::PageUp => app.menu.jump(-i32::from(app.menu_area.height.max(1))),
+            KeyCode::Enter => app.open_selected_camera(),
+            _ => {}
+        },
+    }
+
+    false
+}
+
+/// Does what a key of the keymap stands for in the viewer, returning whether to quit
+fn run_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Quit => return request_quit(app),
+        Action::Help => app.show_help = true,
+        Action::CommandLine => app.command = Some(String::new()),
+        Action::AdjustMode => app.show_settings = true,
+        Action::Pause => {
+            app.paused = !app.paused;
+            app.auto_paused = false;
+            app.last_frame = None;
+        }
+        Action::Step if app.paused => match app.source.as_mut().and_then(|s| s.animation_mut()) {
+            Some(animation) => animation.step(1),
+            // dropping the frozen frame makes the viewer grab exactly one new one
+            None => app.last_frame = None,
+        },
+        Action::Step => {}
+        Action::Slower => app.change_speed(-1),
+        Action::Faster => app.change_speed(1),
+        Action::Loop => app.mark_loop(),
+        Action::Fullscreen => app.fullscreen = !app.fullscreen,
+        Action::EditCharset => {
+            let ramp = app.settings.charset.ramp.iter().collect();
+            app.charset_editor = Some(ramp);
+        }
+        Action::FlipAb => app.flip_ab(),
+        Action::Snapshot => app.snapshot(),
+        Action::Burst => app.start_burst(),
+        Action::SelfTimer => app.self_timer.toggle(),
+        Action::SelfTimerLength => {
+            app.self_timer.cycle();
+            app.toasts
+                .push(format!("Self-timer: {}s", app.self_timer.seconds));
+        }
+        Action::Reset => app.reset_to_defaults(),
+        Action::Record => app.toggle_recording(),
+        Action::Grid => app.grid = app.grid.next(),
+        Action::Markers => app.markers = app.markers.next(),
+        Action::Faces => app.toggle_faces(),
+        Action::Backdrop => app.toggle_backdrop(),
+        Action::ClearMasks => {
+            app.privacy.masks.clear();
+            app.toasts.push("Removed the privacy regions");
+        }
+        Action::AutoPause => {
+            app.auto_pause = !app.auto_pause;
+            app.toasts
+                .push(format!("Auto-pause {}", on_off(app.auto_pause)));
+        }
+        Action::Qr => {
+            app.qr = match app.qr {
+                Some(_) => None,
+                None => Some(QrScanner::default()),
+            };
+            // a paused frame has to be rendered again to be scanned
+            app.render_cache = None;
+        }
+        Action::FewerPixels => app.adjust_cell_pixels(-1),
+        Action::MorePixels => app.adjust_cell_pixels(1),
+        Action::Deinterlace => app.cycle_deinterlace(),
+        Action::Microphone => app.toggle_microphone(),
+        Action::Reactive => app.cycle_reactive(),
+        Action::Stabilize => {
+            app.stabilizer = match app.stabilizer {
+                Some(_) => None,
+                None => Some(Stabilizer::default()),
             };
+            app.toasts.push(format!(
+                "Stabilization {}",
+                on_off(app.stabilizer.is_some())
+            ));
+        }
+        Action::Prompter => app.toggle_prompter(),
+        Action::Split => app.split = !app.split,
+        Action::Retro => app.cycle_retro(),
+        Action::Ordered => app.cycle_ordered(),
+        Action::Braille => app.toggle_braille(),
+        Action::NextRamp => app.next_ramp(),
+        Action::Edges => app.toggle_edges(),
+        Action::Color => app.cycle_color(),
+        Action::Clock => {
+            app.clock = match app.clock {
+                Some(_) => None,
+                None => Some(app.clock_corner),
+            }
+        }
+        Action::Histogram => {
+            app.show_histogram = !app.show_histogram;
+            // a paused frame has to be rendered again to fill in the histogram
+            app.render_cache = None;
+        }
+        Action::Settings => app.show_settings = !app.show_settings,
+    }
+    false
+}
+
+/// Returns `true` when the app can quit right away, otherwise asks what to do
+/// with the recording in progress first
+fn request_quit(app: &mut App) -> bool {
+    if app.recorder.is_none() {
+        return true;
+    }
+
+    app.dialog = Some(Dialog::quit_while_recording());
+    false
+}
+
+/// Applies a key press to the open dialog, returning `true` when the user chose to quit
+fn handle_dialog_key(app: &mut App, key: KeyEvent) -> bool {
+    let dialog = match app.dialog.as_mut() {
+        Some(dialog) => dialog,
+        None => return false,
+    };
+
+    match key.code {
+        KeyCode::Left | KeyCode::Up | KeyCode::BackTab | KeyCode::Char('h') => dialog.previous(),
+        KeyCode::Right | KeyCode::Down | KeyCode::Tab | KeyCode::Char('l') => dialog.next(),
+        KeyCode::Esc => app.dialog = None,
+        KeyCode::Enter => {
+            let (kind, choice) = (dialog.kind, dialog.choice());
+            let message = std::mem::take(&mut dialog.message);
+            app.dialog = None;
+
+            match (kind, choice) {
+                (DialogKind::CameraError, "Retry") => app.open_selected_camera(),
+                (DialogKind::QrCode, "Copy") => match clipboard::copy(&message) {
+                    Ok(()) => app.toasts.push("Copied to the clipboard"),
+                    Err(e) => app.toasts.push(format!("Could not copy: {}", e)),
+                },
+                // stay around to report it when the recording could not be saved
+                (DialogKind::QuitWhileRecording, "Save") if app.stop_recording() => return true,
+                (DialogKind::QuitWhileRecording, "Discard") => {
+                    if let Some(recorder) = app.recorder.take() {
+                        // quitting anyway, nothing is left to show the error on
+                        let _ = recorder.discard();
+                    }
+                    return true;
+                }
+                (DialogKind::DeleteSnapshot, "Delete") => {
+                    if let Some(Err(e)) = app.gallery.as_mut().map(|g| g.delete_selected()) {
+                        app.toasts
+                            .push(format!("Could not delete the snapshot: {}", e));
+                    }
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+
+    false
+}
+
+fn handle_open_prompt_key(app: &mut App, key: KeyEvent) {
+    let path = match app.open_prompt.as_mut() {
+        Some(path) => path,
+        None => return,
+    };
+
+    match key.code {
+        KeyCode::Char(c) => path.push(c),
+        KeyCode::Backspace => {
+            path.pop();
+        }
+        KeyCode::Enter => {
+            let path = std::mem::take(path);
+            app.open_prompt = None;
+            app.open_path(&path);
+        }
+        KeyCode::Esc => app.open_prompt = None,
+        _ => {}
+    }
+}
 
-            let paragraph = Paragraph::new(dithered_text)
-                .block(Block::default().borders(Borders::ALL).title(title));
+/// Types pasted text into whatever input is open. With none open in the menu,
+/// the text is taken as a path, which is what dropping a file on the terminal pastes.
+fn handle_paste(app: &mut App, text: &str) {
+    // inputs are single lines
+    let text = text.trim_end_matches(['\r', '\n']);
+
+    if let Some(path) = app.open_prompt.as_mut() {
+        path.push_str(text);
+    } else if let Some(ramp) = app.charset_editor.as_mut() {
+        ramp.push_str(text);
+    } else if let Some(line) = app.command.as_mut() {
+        line.push_str(text);
+    } else if let Some(name) = app.gallery.as_mut().and_then(|g| g.renaming.as_mut()) {
+        name.push_str(text);
+    } else if app.filtering {
+        app.filter.push_str(text);
+        app.apply_filter();
+    } else if app.source.is_none()
+        && app.gallery.is_none()
+        && app.monitor.is_none()
+        && app.calibration.is_none()
+        && app.dialog.is_none()
+        && app.opening.is_none()
+        && !app.show_help
+    {
+        app.open_prompt = Some(text.to_string());
+    }
+}
+
+fn handle_gallery_key(app: &mut App, key: KeyEvent) {
+    let gallery = match app.gallery.as_mut() {
+        Some(gallery) => gallery,
+        None => return,
+    };
+
+    if let Some(name) = gallery.renaming.as_mut() {
+        match key.code {
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Enter => {
+                let name = std::mem::take(name);
+                gallery.renaming = None;
+                if let Err(e) = gallery.rename_selected(&name) {
+                    app.toasts
+                        .push(format!("Could not rename the snapshot: {}", e));
+                }
+            }
+            KeyCode::Esc => gallery.renaming = None,
+            _ => {}
+        }
+        return;
+    }
 
-            f.render_widget(paragraph, size);
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => gallery.next(),
+        KeyCode::Up | KeyCode::Char('k') => gallery.previous(),
+        KeyCode::Char('r') => {
+            if let Some(stem) = gallery.selected().and_then(|p| p.file_stem()) {
+                gallery.renaming = Some(stem.to_string_lossy().into_owned());
+            }
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            if let Some(name) = gallery.selected().and_then(|p| p.file_name()) {
+                app.dialog = Some(Dialog::delete_snapshot(&name.to_string_lossy()));
+            }
         }
+        KeyCode::Esc | KeyCode::Char('g') => app.gallery = None,
+        _ => {}
     }
 }
 
-const ASCII_CHARS: &str = " .:-=+*#%@";
+/// Edits and runs the `:` command line, returning `true` when it asked to quit
+fn handle_command_key(app: &mut App, key: KeyEvent) -> bool {
+    let line = match app.command.as_mut() {
+        Some(line) => line,
+        None => return false,
+    };
+
+    match key.code {
+        KeyCode::Char(c) => line.push(c),
+        // like in vim, erasing past the `:` leaves the command line
+        KeyCode::Backspace if line.is_empty() => app.command = None,
+        KeyCode::Backspace => {
+            line.pop();
+        }
+        KeyCode::Esc => app.command = None,
+        KeyCode::Enter => {
+            let command = Command::parse(line);
+            app.command = None;
+
+            match command {
+                Ok(Command::Quit) => return request_quit(app),
+                Ok(Command::Snapshot) => app.snapshot(),
+                Ok(Command::Charset(charset)) => app.settings.charset = charset,
+                Ok(Command::Fps(fps)) => app.settings.target_fps = fps,
+                Ok(Command::Reset) => app.reset_to_defaults(),
+                Err(e) => app.toasts.push(e),
+            }
+        }
+        _ => {}
+    }
 
-fn dither_image(image: DynamicImage) -> String {
-    let (width, height) = image.dimensions();
+    false
+}
 
-    let binding = image.grayscale();
-    let image = match binding.as_luma8() {
-        Some(img) => img,
-        None => panic!("Image error"),
+fn handle_charset_editor_key(app: &mut App, key: KeyEvent) {
+    let ramp = match app.charset_editor.as_mut() {
+        Some(ramp) => ramp,
+        None => return,
     };
 
-    let mut ascii_image: Vec<Vec<u8>> = vec![vec![0; width as usize]; height as usize];
-
-    // normalize the image to the range [0, 1]
-    let min = image.iter().min().unwrap();
-    let max = image.iter().max().unwrap();
-    let norm_image = ImageBuffer::from_fn(width, height, |x, y| {
-        let pixel = image.get_pixel(x, y);
-        let value = (pixel[0] as f32 - *min as f32) / (max - min) as f32;
-        image::Luma([(value * 255.0) as u8])
-    });
-
-    // scale the image to the range of ASCII characters
-    let scale_image = ImageBuffer::from_fn(width, height, |x, y| {
-        let pixel = norm_image.get_pixel(x, y);
-        let value = (pixel[0] as f32 / 255.0 * (ASCII_CHARS.len() - 1) as f32).round() as u8;
-        image::Luma([value])
-    });
-
-    // replace the pixel values with their corresponding ASCII characters
-    for (x, y, pixel) in scale_image.enumerate_pixels() {
-        let value = pixel[0];
-        let ascii_char = ASCII_CHARS.chars().nth(value as usize).unwrap();
-        ascii_image[y as usize][x as usize] = ascii_char as u8;
-    }
-
-    // save and return the resulting ascii art
-    let mut output = String::new();
-    for row in ascii_image {
-        let mut row_string: String = row.iter().map(|c| *c as char).collect();
-        row_string.push('\n');
-        output.push_str(&row_string);
-    }
-    output
+    match key.code {
+        KeyCode::Char(c) => ramp.push(c),
+        KeyCode::Backspace => {
+            ramp.pop();
+        }
+        KeyCode::Enter => match Charset::parse("custom", ramp) {
+            Ok(charset) => {
+                app.settings.charset = charset;
+                app.charset_editor = None;
+            }
+            Err(e) => app.toasts.push(e),
+        },
+        // puts the characters in order, for a ramp typed from memory
+        KeyCode::Tab => match Charset::new("custom", ramp).sorted() {
+            Some(sorted) => *ramp = sorted.ramp.iter().collect(),
+            None => app
+                .toasts
+                .push("Only ramps of ASCII, blocks and braille can be sorted"),
+        },
+        KeyCode::Esc => app.charset_editor = None,
+        _ => {}
+    }
+}
+
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    if app.show_help
+        || app.dialog.is_some()
+        || app.opening.is_some()
+        || app.charset_editor.is_some()
+        || app.command.is_some()
+        || app.gallery.is_some()
+        || app.monitor.is_some()
+        || app.calibration.is_some()
+        || app.open_prompt.is_some()
+    {
+        return;
+    }
+
+    match app.source {
+        None => {
+            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                if !contains(app.menu_area, mouse.column, mouse.row) {
+                    return;
+                }
+
+                let row = (mouse.row - app.menu_area.y).into();
+                if let Some(index) = app.menu.item_at(row) {
+                    app.menu.state.select(Some(index));
+                    app.open_selected_camera();
+                }
+            }
+        }
+        Some(_) => match mouse.kind {
+            MouseEventKind::ScrollUp => app.viewport.zoom_by(1.25),
+            MouseEventKind::ScrollDown => app.viewport.zoom_by(0.8),
+            MouseEventKind::Down(MouseButton::Left)
+                if contains(app.view_area, mouse.column, mouse.row) =>
+            {
+                app.drag_origin = Some((mouse.column, mouse.row));
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((x, y)) = app.drag_origin {
+                    let view = app.view_area;
+                    let dx = (mouse.column as f32 - x as f32) / view.width.max(1) as f32;
+                    let dy = (mouse.row as f32 - y as f32) / view.height.max(1) as f32;
+
+                    app.pan(dx, dy);
+                    app.drag_origin = Some((mouse.column, mouse.row));
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => app.drag_origin = None,
+            MouseEventKind::Down(MouseButton::Right)
+                if contains(app.view_area, mouse.column, mouse.row) =>
+            {
+                let cell = (mouse.column, mouse.row);
+                app.mask_drag = Some((cell, cell));
+            }
+            MouseEventKind::Drag(MouseButton::Right) => {
+                if let Some((start, _)) = app.mask_drag {
+                    app.mask_drag = Some((start, (mouse.column, mouse.row)));
+                }
+            }
+            MouseEventKind::Up(MouseButton::Right) => app.finish_mask(),
+            _ => {}
+        },
+    }
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
 }
@@ -0,0 +1,85 @@
+use image::{imageops, GrayImage};
+use std::time::{Duration, Instant};
+
+use crate::source::RgbFrame;
+
+/// Frames are compared at this size, which smooths out sensor noise and keeps
+/// the comparison cheap
+const SAMPLE_WIDTH: u32 = 32;
+const SAMPLE_HEIGHT: u32 = 24;
+
+/// Mean luma difference between two samples above which they count as motion
+const THRESHOLD: f32 = 4.0;
+
+/// How long the camera has to see nothing move before motion counts as a new event
+const EVENT_GAP: Duration = Duration::from_secs(5);
+
+/// Tells whether anything moved in front of the camera lately
+pub struct MotionDetector {
+    previous: Option<GrayImage>,
+    last_motion: Instant,
+    /// When the frames last differed, leaving out user activity
+    last_moved: Option<Instant>,
+    /// Score of the motion that started after a quiet spell, until it is taken
+    event: Option<f32>,
+}
+
+impl Default for MotionDetector {
+    fn default() -> MotionDetector {
+        MotionDetector {
+            previous: None,
+            last_motion: Instant::now(),
+            last_moved: None,
+            event: None,
+        }
+    }
+}
+
+impl MotionDetector {
+    /// Compares a new frame with the previous one
+    pub fn update(&mut self, frame: &RgbFrame) {
+        let sample = imageops::grayscale(&imageops::thumbnail(frame, SAMPLE_WIDTH, SAMPLE_HEIGHT));
+        self.compare(sample);
+    }
+
+    /// Same as `update`, for a frame that is luma only
+    pub fn update_luma(&mut self, frame: &GrayImage) {
+        self.compare(imageops::thumbnail(frame, SAMPLE_WIDTH, SAMPLE_HEIGHT));
+    }
+
+    fn compare(&mut self, sample: GrayImage) {
+        if let Some(previous) = self.previous.as_ref() {
+            let total: u32 = previous
+                .pixels()
+                .zip(sample.pixels())
+                .map(|(a, b)| a[0].abs_diff(b[0]) as u32)
+                .sum();
+            let mean = total as f32 / (SAMPLE_WIDTH * SAMPLE_HEIGHT) as f32;
+
+            if mean > THRESHOLD {
+                if self.last_moved.is_none_or(|t| t.elapsed() >= EVENT_GAP) {
+                    self.event = Some(mean);
+                }
+                self.last_motion = Instant::now();
+                self.last_moved = Some(self.last_motion);
+            }
+        }
+
+        self.previous = Some(sample);
+    }
+
+    /// Counts user activity, such as a key press, as motion
+    pub fn touch(&mut self) {
+        self.last_motion = Instant::now();
+    }
+
+    pub fn still_for(&self) -> Duration {
+        self.last_motion.elapsed()
+    }
+
+    /// The score, the mean luma difference between two frames, of motion that
+    /// started since the last call after the camera saw nothing move for a while
+    pub fn take_event(&mut self) -> Option<f32> {
+        self.event.take()
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where motion events are published, and as whom
+#[derive(Clone)]
+pub struct Broker {
+    /// `host:port`, the port being 1883 for most brokers
+    pub address: String,
+    pub topic: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Publishes messages to an MQTT broker from a background thread, so that a
+/// slow or unreachable broker never holds up the viewer. Messages are sent at
+/// most once (QoS 0), which is what Home Assistant style automations expect of
+/// sensor events. Each one gets a session of its own: events can be hours
+/// apart, and a connection left open that long may have been dropped by the
+/// broker or a router without a word, losing the event written to it.
+pub struct Publisher {
+    sender: Sender<String>,
+    /// Why messages could not be sent, to be shown to the user
+    pub errors: Receiver<String>,
+}
+
+impl Publisher {
+    pub fn start(broker: Broker) -> Publisher {
+        let (sender, messages) = mpsc::channel::<String>();
+        let (error_sender, errors) = mpsc::channel();
+
+        thread::spawn(move || {
+            for payload in messages {
+                if let Err(e) = send(&broker, &payload) {
+                    let _ = error_sender.send(format!("{}: {}", broker.address, e));
+                }
+            }
+        });
+
+        Publisher { sender, errors }
+    }
+
+    pub fn publish(&self, payload: String) {
+        // the thread only stops with the publisher
+        let _ = self.sender.send(payload);
+    }
+}
+
+/// Opens a session, publishes the message and closes the session again
+fn send(broker: &Broker, payload: &str) -> io::Result<()> {
+    let mut stream = connect(broker)?;
+    publish(&mut stream, &broker.topic, payload)?;
+    stream.write_all(&packet(0xE0, &[]))
+}
+
+/// Opens a session with the broker, MQTT 3.1.1
+fn connect(broker: &Broker) -> io::Result<TcpStream> {
+    let address = broker
+        .address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown host"))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.write_all(&connect_packet(broker, &client_id()))?;
+
+    // CONNACK: the packet type, its length, the session flag and the return code
+    let mut ack = [0; 4];
+    stream.read_exact(&mut ack)?;
+    match ack {
+        [0x20, 2, _, 0] => Ok(stream),
+        [0x20, 2, _, 4 | 5] => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the broker refused the username or password",
+        )),
+        [0x20, 2, _, code] => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("the broker refused the connection ({})", code),
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an MQTT broker",
+        )),
+    }
+}
+
+fn client_id() -> String {
+    format!("ascii-camera-{}", std::process::id())
+}
+
+/// CONNECT for a clean session, with the keep alive turned off since the
+/// session ends with the message
+fn connect_packet(broker: &Broker, client_id: &str) -> Vec<u8> {
+    let mut flags = 0x02;
+    let mut body = Vec::new();
+    write_string(&mut body, "MQTT");
+    body.push(4);
+    if broker.username.is_some() {
+        flags |= 0x80;
+    }
+    if broker.password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&0u16.to_be_bytes());
+
+    write_string(&mut body, client_id);
+    if let Some(username) = broker.username.as_ref() {
+        write_string(&mut body, username);
+    }
+    if let Some(password) = broker.password.as_ref() {
+        write_string(&mut body, password);
+    }
+    packet(0x10, &body)
+}
+
+fn publish(stream: &mut TcpStream, topic: &str, payload: &str) -> io::Result<()> {
+    let mut body = Vec::new();
+    write_string(&mut body, topic);
+    body.extend_from_slice(payload.as_bytes());
+    stream.write_all(&packet(0x30, &body))
+}
+
+/// Prefixes a packet body with its type and its length, 7 bits per byte
+fn packet(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![kind];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Strings are UTF-8 prefixed by their length on two bytes
+fn write_string(buffer: &mut Vec<u8>, text: &str) {
+    buffer.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(text.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broker(username: Option<&str>, password: Option<&str>) -> Broker {
+        Broker {
+            address: String::from("localhost:1883"),
+            topic: String::from("motion"),
+            username: username.map(String::from),
+            password: password.map(String::from),
+        }
+    }
+
+    #[test]
+    fn remaining_lengths_take_seven_bits_a_byte() {
+        let header = |length: usize| {
+            let packet = packet(0x30, &vec![0; length]);
+            packet[..packet.len() - length].to_vec()
+        };
+        assert_eq!(header(0), vec![0x30, 0x00]);
+        assert_eq!(header(127), vec![0x30, 0x7F]);
+        assert_eq!(header(128), vec![0x30, 0x80, 0x01]);
+        assert_eq!(header(16383), vec![0x30, 0xFF, 0x7F]);
+        assert_eq!(header(16384), vec![0x30, 0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn strings_are_prefixed_by_their_length() {
+        let mut buffer = Vec::new();
+        write_string(&mut buffer, "é!");
+        assert_eq!(buffer, vec![0, 3, 0xC3, 0xA9, b'!']);
+    }
+
+    #[test]
+    fn connects_without_credentials() {
+        let mut expected = vec![0x10, 14, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 0, 0, 2];
+        expected.extend_from_slice(b"id");
+        assert_eq!(connect_packet(&broker(None, None), "id"), expected);
+    }
+
+    #[test]
+    fn connects_with_credentials() {
+        let mut expected = vec![0x10, 25, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xC2, 0, 0, 0, 2];
+        expected.extend_from_slice(b"id");
+        expected.extend_from_slice(&[0, 3]);
+        expected.extend_from_slice(b"cam");
+        expected.extend_from_slice(&[0, 4]);
+        expected.extend_from_slice(b"pass");
+        assert_eq!(
+            connect_packet(&broker(Some("cam"), Some("pass")), "id"),
+            expected
+        );
+
+        let packet = connect_packet(&broker(Some("cam"), None), "id");
+        assert_eq!(packet[9], 0x82);
+    }
+}
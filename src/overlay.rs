@@ -0,0 +1,323 @@
+use tui::style::Color;
+
+use crate::canvas::{Canvas, Cell};
+use crate::faces::Face;
+use crate::logo::Logo;
+
+const OVERLAY_COLOR: Color = Color::Cyan;
+
+/// Composition guides drawn over the image
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Grid {
+    Off,
+    /// Rule of thirds
+    Thirds,
+    Quarters,
+}
+
+impl Grid {
+    pub fn next(self) -> Grid {
+        match self {
+            Grid::Off => Grid::Thirds,
+            Grid::Thirds => Grid::Quarters,
+            Grid::Quarters => Grid::Off,
+        }
+    }
+
+    fn divisions(self) -> usize {
+        match self {
+            Grid::Off => 1,
+            Grid::Thirds => 3,
+            Grid::Quarters => 4,
+        }
+    }
+}
+
+/// Alignment markers drawn over the image
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Markers {
+    Off,
+    Crosshair,
+    /// Corners of the area inset by 5% on every side
+    SafeArea,
+    Both,
+}
+
+impl Markers {
+    pub fn next(self) -> Markers {
+        match self {
+            Markers::Off => Markers::Crosshair,
+            Markers::Crosshair => Markers::SafeArea,
+            Markers::SafeArea => Markers::Both,
+            Markers::Both => Markers::Off,
+        }
+    }
+}
+
+/// Where a small overlay such as the clock is drawn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub fn from_name(name: &str) -> Option<Corner> {
+        match name {
+            "top-left" => Some(Corner::TopLeft),
+            "top-right" => Some(Corner::TopRight),
+            "bottom-left" => Some(Corner::BottomLeft),
+            "bottom-right" => Some(Corner::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+fn overlay_cell(ch: char) -> Cell {
+    Cell {
+        ch,
+        fg: Some(OVERLAY_COLOR),
+        bg: None,
+    }
+}
+
+pub fn draw_grid(canvas: &mut Canvas, grid: Grid) {
+    let divisions = grid.divisions();
+    let columns: Vec<usize> = (1..divisions)
+        .map(|i| canvas.width * i / divisions)
+        .collect();
+    let rows: Vec<usize> = (1..divisions)
+        .map(|i| canvas.height * i / divisions)
+        .collect();
+
+    for &y in &rows {
+        for x in 0..canvas.width {
+            canvas.set(x, y, overlay_cell('-'));
+        }
+    }
+
+    for &x in &columns {
+        for y in 0..canvas.height {
+            let ch = if rows.contains(&y) { '+' } else { '|' };
+            canvas.set(x, y, overlay_cell(ch));
+        }
+    }
+}
+
+pub fn draw_markers(canvas: &mut Canvas, markers: Markers) {
+    if matches!(markers, Markers::Crosshair | Markers::Both) {
+        draw_crosshair(canvas);
+    }
+    if matches!(markers, Markers::SafeArea | Markers::Both) {
+        draw_safe_area(canvas);
+    }
+}
+
+fn draw_crosshair(canvas: &mut Canvas) {
+    let (cx, cy) = (canvas.width / 2, canvas.height / 2);
+    // cells are about twice as tall as they are wide
+    let arm_x = (canvas.width / 20).max(1);
+    let arm_y = (arm_x / 2).max(1);
+
+    for x in cx.saturating_sub(arm_x)..=cx + arm_x {
+        canvas.set(x, cy, overlay_cell('-'));
+    }
+    for y in cy.saturating_sub(arm_y)..=cy + arm_y {
+        canvas.set(cx, y, overlay_cell('|'));
+    }
+    canvas.set(cx, cy, overlay_cell('+'));
+}
+
+fn draw_safe_area(canvas: &mut Canvas) {
+    if canvas.width < 4 || canvas.height < 4 {
+        return;
+    }
+
+    let left = canvas.width * 5 / 100;
+    let right = canvas.width - 1 - left;
+    let top = canvas.height * 5 / 100;
+    let bottom = canvas.height - 1 - top;
+    let arm_x = (canvas.width / 16).max(1);
+    let arm_y = (canvas.height / 16).max(1);
+
+    for (x, y, dx, dy) in [
+        (left, top, 1, 1),
+        (right, top, -1, 1),
+        (left, bottom, 1, -1),
+        (right, bottom, -1, -1),
+    ] {
+        for i in 1..=arm_x {
+            let x = (x as isize + dx * i as isize) as usize;
+            canvas.set(x, y, overlay_cell('-'));
+        }
+        for i in 1..=arm_y {
+            let y = (y as isize + dy * i as isize) as usize;
+            canvas.set(x, y, overlay_cell('|'));
+        }
+        canvas.set(x, y, overlay_cell('+'));
+    }
+}
+
+/// 3x5 bitmaps of the digits, one string per row
+const DIGITS: [[&str; 5]; 10] = [
+    ["###", "# #", "# #", "# #", "###"],
+    [" # ", "## ", " # ", " # ", "###"],
+    ["###", "  #", "###", "#  ", "###"],
+    ["###", "  #", "###", "  #", "###"],
+    ["# #", "# #", "###", "  #", "  #"],
+    ["###", "#  ", "###", "  #", "###"],
+    ["###", "#  ", "###", "# #", "###"],
+    ["###", "  #", "  #", "  #", "  #"],
+    ["###", "# #", "###", "# #", "###"],
+    ["###", "# #", "###", "  #", "###"],
+];
+
+/// Draws a number in large digits in the middle of the canvas
+pub fn draw_big_number(canvas: &mut Canvas, number: u64) {
+    let digits: Vec<usize> = number
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as usize)
+        .collect();
+
+    // cells are about twice as tall as they are wide, so columns are doubled
+    let width = digits.len() * 8 - 2;
+    let left = canvas.width.saturating_sub(width) / 2;
+    let top = canvas.height.saturating_sub(5) / 2;
+
+    for (i, digit) in digits.iter().enumerate() {
+        for (y, row) in DIGITS[*digit].iter().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                let ch = if pixel == '#' { '█' } else { ' ' };
+                let column = left + i * 8 + x * 2;
+                canvas.set(column, top + y, overlay_cell(ch));
+                canvas.set(column + 1, top + y, overlay_cell(ch));
+            }
+        }
+    }
+}
+
+/// Writes a line of text in a corner, one cell away from the edges
+pub fn draw_label(canvas: &mut Canvas, text: &str, corner: Corner) {
+    let width = text.chars().count();
+    let x = match corner {
+        Corner::TopLeft | Corner::BottomLeft => 1,
+        Corner::TopRight | Corner::BottomRight => canvas.width.saturating_sub(width + 1),
+    };
+    let y = match corner {
+        Corner::TopLeft | Corner::TopRight => 1,
+        Corner::BottomLeft | Corner::BottomRight => canvas.height.saturating_sub(2),
+    };
+
+    for (i, ch) in text.chars().enumerate() {
+        canvas.set(x + i, y, overlay_cell(ch));
+    }
+}
+
+/// Marks the split between the image before and after a change
+pub fn draw_split(canvas: &mut Canvas) {
+    let middle = canvas.width / 2;
+    for y in 0..canvas.height {
+        canvas.set(middle, y, overlay_cell('|'));
+    }
+    draw_label(canvas, "before", Corner::TopLeft);
+    draw_label(canvas, "after", Corner::TopRight);
+}
+
+/// Draws a line of text over the bottom row, on a blank background so it stays readable
+pub fn draw_ticker(canvas: &mut Canvas, text: &str) {
+    let y = canvas.height.saturating_sub(1);
+    for (x, ch) in text.chars().enumerate() {
+        canvas.set(
+            x,
+            y,
+            Cell {
+                ch,
+                fg: Some(OVERLAY_COLOR),
+                bg: Some(Color::Black),
+            },
+        );
+    }
+}
+
+/// Draws the logo in its corner, one cell in from the edges like the labels
+pub fn draw_logo(canvas: &mut Canvas, logo: &Logo) {
+    let left = match logo.corner {
+        Corner::TopLeft | Corner::BottomLeft => 1,
+        Corner::TopRight | Corner::BottomRight => canvas.width.saturating_sub(logo.width + 1),
+    };
+    let top = match logo.corner {
+        Corner::TopLeft | Corner::TopRight => 1,
+        Corner::BottomLeft | Corner::BottomRight => canvas.height.saturating_sub(logo.height + 1),
+    };
+
+    for y in 0..logo.height {
+        for x in 0..logo.width {
+            if let Some(cell) = logo.cell(x, y) {
+                canvas.set(left + x, top + y, cell);
+            }
+        }
+    }
+}
+
+/// Draws a labeled box around every face
+pub fn draw_faces(canvas: &mut Canvas, faces: &[Face]) {
+    let (width, height) = (canvas.width as f32, canvas.height as f32);
+
+    for face in faces {
+        let left = (face.x * width) as usize;
+        let top = (face.y * height) as usize;
+        let right = (((face.x + face.width) * width) as usize).min(canvas.width.saturating_sub(1));
+        let bottom =
+            (((face.y + face.height) * height) as usize).min(canvas.height.saturating_sub(1));
+        if right <= left || bottom <= top {
+            continue;
+        }
+
+        for x in left + 1..right {
+            canvas.set(x, top, overlay_cell('-'));
+            canvas.set(x, bottom, overlay_cell('-'));
+        }
+        for y in top + 1..bottom {
+            canvas.set(left, y, overlay_cell('|'));
+            canvas.set(right, y, overlay_cell('|'));
+        }
+        for (x, y) in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+            canvas.set(x, y, overlay_cell('+'));
+        }
+
+        // the label sits on the top edge when the box is wide enough for it
+        if right - left > 6 {
+            for (i, ch) in "face".chars().enumerate() {
+                canvas.set(left + 2 + i, top, overlay_cell(ch));
+            }
+        }
+    }
+}
+
+/// Draws the outline of a quadrilateral given by its corners, as fractions of the canvas size
+pub fn draw_outline(canvas: &mut Canvas, corners: &[(f32, f32); 4]) {
+    let (width, height) = (canvas.width as f32, canvas.height as f32);
+
+    for i in 0..4 {
+        let (x0, y0) = (corners[i].0 * width, corners[i].1 * height);
+        let (x1, y1) = (
+            corners[(i + 1) % 4].0 * width,
+            corners[(i + 1) % 4].1 * height,
+        );
+
+        // one point per cell along the longer axis
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let x = x0 + (x1 - x0) * t;
+            let y = y0 + (y1 - y0) * t;
+            if x >= 0.0 && y >= 0.0 {
+                canvas.set(x as usize, y as usize, overlay_cell('*'));
+            }
+        }
+    }
+}
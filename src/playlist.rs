@@ -0,0 +1,99 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::source::{clean_path, is_url};
+
+/// How long an item is shown by default before the next one
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(30);
+
+/// Something the playlist shows
+#[derive(Clone, PartialEq, Debug)]
+pub enum Item {
+    /// A camera, by its name in the camera list
+    Camera(String),
+    File(PathBuf),
+}
+
+/// Sources shown one after the other, for unattended displays
+pub struct Playlist {
+    items: Vec<Item>,
+    /// How long every item is shown
+    pub duration: Duration,
+    current: usize,
+    started: Instant,
+}
+
+impl Playlist {
+    /// Reads the items from a list separated by ';', where cameras are written
+    /// `camera:<name>` and anything else is the path of an image file
+    pub fn parse(text: &str, duration: Duration) -> Result<Playlist, String> {
+        let items = text
+            .split(';')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| match item.strip_prefix("camera:") {
+                Some(name) => Ok(Item::Camera(name.trim().to_string())),
+                None if is_url(item) => Err(format!(
+                    "Only cameras and image files can be in the playlist, not URLs: {}",
+                    item
+                )),
+                None => Ok(Item::File(clean_path(item))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if items.is_empty() {
+            return Err(String::from("The playlist is empty"));
+        }
+
+        Ok(Playlist {
+            items,
+            duration,
+            current: 0,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn current(&self) -> &Item {
+        &self.items[self.current]
+    }
+
+    /// Whether the current item has been shown for long enough
+    pub fn due(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    /// Moves on to the next item, going back to the first after the last one
+    pub fn advance(&mut self) -> &Item {
+        self.current = (self.current + 1) % self.items.len();
+        self.started = Instant::now();
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cameras_and_files() {
+        let mut playlist = Playlist::parse(
+            "camera: Front ; ~/art.png;; '/tmp/a b.jpg'",
+            DEFAULT_DURATION,
+        )
+        .unwrap();
+        assert_eq!(playlist.current(), &Item::Camera(String::from("Front")));
+        assert_eq!(playlist.advance(), &Item::File(PathBuf::from("~/art.png")));
+        assert_eq!(
+            playlist.advance(),
+            &Item::File(PathBuf::from("/tmp/a b.jpg"))
+        );
+        assert_eq!(playlist.advance(), &Item::Camera(String::from("Front")));
+    }
+
+    #[test]
+    fn rejects_urls_and_empty_lists() {
+        assert!(Playlist::parse("http://example.com/a.png", DEFAULT_DURATION).is_err());
+        assert!(Playlist::parse(" ; ", DEFAULT_DURATION).is_err());
+    }
+}
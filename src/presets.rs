@@ -0,0 +1,239 @@
+use std::fs;
+
+use crate::config::{config_dir, quote, Config};
+use crate::effects::Effect;
+use crate::retro::Palette;
+use crate::settings::{
+    Charset, ColorMode, DitherAlgorithm, Exposure, LumaWeights, ResizeFilter, Settings, ToneMap,
+};
+
+const PRESETS_FILE: &str = "presets.toml";
+
+/// How many slots the digit keys can reach
+pub const SLOTS: usize = 9;
+
+/// Saved looks, applied with the digit keys. The frame rate is not part of a look
+/// and is left out.
+#[derive(Default)]
+pub struct Presets {
+    slots: [Option<Settings>; SLOTS],
+}
+
+impl Presets {
+    /// Loads the saved presets, a missing or unreadable file leaving every slot empty
+    pub fn load() -> Presets {
+        let mut presets = Presets::default();
+
+        let text = match config_dir().map(|dir| fs::read_to_string(dir.join(PRESETS_FILE))) {
+            Some(Ok(text)) => text,
+            _ => return presets,
+        };
+        let config = match Config::parse(&text) {
+            Ok(config) => config,
+            Err(_) => return presets,
+        };
+
+        for (i, slot) in presets.slots.iter_mut().enumerate() {
+            *slot = read_settings(&config, &(i + 1).to_string());
+        }
+        presets
+    }
+
+    /// Returns the preset in a slot, counted from 1
+    pub fn get(&self, slot: usize) -> Option<&Settings> {
+        self.slots.get(slot.checked_sub(1)?)?.as_ref()
+    }
+
+    /// Stores the settings in a slot, counted from 1, and writes every preset to disk
+    pub fn save(&mut self, slot: usize, settings: &Settings) -> Result<(), String> {
+        match slot.checked_sub(1).and_then(|i| self.slots.get_mut(i)) {
+            Some(s) => *s = Some(settings.clone()),
+            None => return Err(format!("There is no preset slot {}", slot)),
+        }
+
+        let dir = config_dir().ok_or("Could not find the configuration directory")?;
+        let mut text = String::new();
+        for (i, settings) in self.slots.iter().enumerate() {
+            if let Some(settings) = settings {
+                write_settings(&mut text, &(i + 1).to_string(), settings);
+            }
+        }
+
+        fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(dir.join(PRESETS_FILE), text))
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn read_settings(config: &Config, section: &str) -> Option<Settings> {
+    let get = |key: &str| config.get(&format!("{}.{}", section, key));
+    let defaults = Settings::default();
+
+    let charset = match (get("charset"), get("ramp")) {
+        (Some(name), Some(ramp)) => Charset::parse(name, ramp).ok()?,
+        _ => return None,
+    };
+
+    Some(Settings {
+        brightness: get("brightness")?.parse().ok()?,
+        contrast: get("contrast")?.parse().ok()?,
+        gamma: get("gamma")?.parse().ok()?,
+        // presets saved before these settings existed do not have them
+        exposure: get("exposure")
+            .and_then(Exposure::from_name)
+            .unwrap_or(defaults.exposure),
+        clip: get("clip")
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(defaults.clip),
+        local_contrast: get("local_contrast")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(defaults.local_contrast),
+        tone_map: get("tone_map")
+            .and_then(ToneMap::from_name)
+            .unwrap_or(defaults.tone_map),
+        effect: get("effect")
+            .and_then(Effect::from_name)
+            .unwrap_or(defaults.effect),
+        glitch: get("glitch")
+            .and_then(|g| g.parse().ok())
+            .unwrap_or(defaults.glitch),
+        filter: get("filter")
+            .and_then(ResizeFilter::from_name)
+            .unwrap_or(defaults.filter),
+        luma: get("luma")
+            .and_then(LumaWeights::from_name)
+            .unwrap_or(defaults.luma),
+        algorithm: DitherAlgorithm::from_name(get("algorithm")?)?,
+        charset,
+        edges: get("edges")
+            .and_then(|e| e.parse().ok())
+            .unwrap_or(defaults.edges),
+        color_mode: ColorMode::from_name(get("color_mode")?)?,
+        color_boost: get("color_boost")
+            .and_then(|b| b.parse().ok())
+            .unwrap_or(defaults.color_boost),
+        crt: get("crt")
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(defaults.crt),
+        retro: get("retro").and_then(Palette::from_name),
+        braille: get("braille")
+            .and_then(|b| b.parse().ok())
+            .unwrap_or(defaults.braille),
+        mirror: get("mirror")?.parse().ok()?,
+        invert: get("invert")?.parse().ok()?,
+        ..defaults
+    })
+}
+
+/// Writes the look as a section of the configuration format
+pub fn write_settings(text: &mut String, section: &str, settings: &Settings) {
+    let ramp: String = settings.charset.ramp.iter().collect();
+
+    text.push_str(&format!("[{}]\n", section));
+    text.push_str(&format!("brightness = {}\n", settings.brightness));
+    text.push_str(&format!("contrast = {}\n", settings.contrast));
+    text.push_str(&format!("gamma = {}\n", settings.gamma));
+    text.push_str(&format!("exposure = {}\n", quote(settings.exposure.name())));
+    text.push_str(&format!("clip = {}\n", settings.clip));
+    text.push_str(&format!("local_contrast = {}\n", settings.local_contrast));
+    text.push_str(&format!("tone_map = {}\n", quote(settings.tone_map.name())));
+    text.push_str(&format!("effect = {}\n", quote(&settings.effect.name())));
+    text.push_str(&format!("glitch = {}\n", settings.glitch));
+    text.push_str(&format!("filter = {}\n", quote(settings.filter.name())));
+    text.push_str(&format!("luma = {}\n", quote(&settings.luma.name())));
+    text.push_str(&format!(
+        "algorithm = {}\n",
+        quote(settings.algorithm.name())
+    ));
+    text.push_str(&format!("charset = {}\n", quote(&settings.charset.name)));
+    text.push_str(&format!("ramp = {}\n", quote(&ramp)));
+    text.push_str(&format!("edges = {}\n", settings.edges));
+    text.push_str(&format!(
+        "color_mode = {}\n",
+        quote(settings.color_mode.name())
+    ));
+    text.push_str(&format!("color_boost = {}\n", settings.color_boost));
+    text.push_str(&format!("crt = {}\n", settings.crt));
+    // no key means no retro palette
+    if let Some(palette) = settings.retro {
+        text.push_str(&format!("retro = {}\n", quote(palette.name())));
+    }
+    text.push_str(&format!("braille = {}\n", settings.braille));
+    text.push_str(&format!("mirror = {}\n", settings.mirror));
+    text.push_str(&format!("invert = {}\n\n", settings.invert));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_back(settings: &Settings) -> Option<Settings> {
+        let mut text = String::new();
+        write_settings(&mut text, "preset-1", settings);
+        read_settings(&Config::parse(&text).unwrap(), "preset-1")
+    }
+
+    #[test]
+    fn settings_are_read_back() {
+        assert_eq!(read_back(&Settings::default()), Some(Settings::default()));
+
+        let settings = Settings {
+            brightness: -0.25,
+            contrast: 1.5,
+            gamma: 0.8,
+            exposure: Exposure::Auto,
+            clip: 2.5,
+            local_contrast: true,
+            tone_map: ToneMap::Hable,
+            effect: Effect::Kaleidoscope(6),
+            glitch: 0.3,
+            filter: ResizeFilter::Nearest,
+            luma: LumaWeights::Custom([0.5, 0.25, 0.25]),
+            algorithm: DitherAlgorithm::Ordered(8),
+            charset: Charset::new("quoted \"ramp\"", " .\\\"#"),
+            edges: true,
+            color_mode: ColorMode::Truecolor,
+            color_boost: true,
+            crt: true,
+            retro: Some(Palette::Cga),
+            braille: true,
+            mirror: true,
+            invert: true,
+            ..Settings::default()
+        };
+        assert_eq!(read_back(&settings), Some(settings));
+    }
+
+    #[test]
+    fn older_presets_get_the_defaults_of_newer_settings() {
+        let text = "[preset-1]\n\
+            brightness = 0.5\n\
+            contrast = 1\n\
+            gamma = 1\n\
+            algorithm = \"floyd-steinberg\"\n\
+            charset = \"standard\"\n\
+            ramp = \" .:-=+*#%@\"\n\
+            color_mode = \"mono\"\n\
+            mirror = true\n\
+            invert = false\n";
+        let settings = read_settings(&Config::parse(text).unwrap(), "preset-1").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                brightness: 0.5,
+                algorithm: DitherAlgorithm::FloydSteinberg,
+                mirror: true,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn presets_missing_a_required_setting_are_skipped() {
+        let text = "[preset-1]\nbrightness = 0.5\n";
+        assert_eq!(
+            read_settings(&Config::parse(text).unwrap(), "preset-1"),
+            None
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use image::DynamicImage;
+use nokhwa::{pixel_format::RgbFormat, utils::CameraIndex, Camera, NokhwaError};
+use std::{
+    sync::mpsc::{Receiver, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use crate::app::open_small_camera_async;
+use crate::dither::dither_image;
+use crate::settings::Settings;
+
+const REFRESH_RATE: Duration = Duration::from_millis(500);
+
+/// A small, slowly refreshed rendering of the camera highlighted in the menu
+pub struct Preview {
+    /// Position of the previewed camera in the camera list
+    pub index: usize,
+    camera: Option<Camera>,
+    /// The camera while it is still being opened
+    pending: Option<Receiver<Result<Camera, NokhwaError>>>,
+    pub text: String,
+    updated: Option<Instant>,
+}
+
+impl Preview {
+    pub fn open(index: usize, camera_index: &CameraIndex) -> Preview {
+        Preview {
+            index,
+            camera: None,
+            // the preview is too small for the format or resolution to matter
+            pending: Some(open_small_camera_async(camera_index)),
+            text: String::from("Opening..."),
+            updated: None,
+        }
+    }
+
+    /// Grabs and renders a new frame if the previous one is old enough
+    pub fn refresh(&mut self, width: u16, height: u16, settings: &Settings) {
+        if let Some(pending) = self.pending.as_ref() {
+            match pending.try_recv() {
+                Ok(Ok(camera)) => self.camera = Some(camera),
+                Ok(Err(_)) | Err(TryRecvError::Disconnected) => {
+                    self.text = String::from("No preview available")
+                }
+                Err(TryRecvError::Empty) => return,
+            }
+            self.pending = None;
+        }
+
+        let camera = match self.camera.as_mut() {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        if let Some(updated) = self.updated {
+            if updated.elapsed() < REFRESH_RATE {
+                return;
+            }
+        }
+        self.updated = Some(Instant::now());
+
+        let decoded = match camera.frame().and_then(|f| f.decode_image::<RgbFormat>()) {
+            Ok(decoded) => decoded,
+            Err(_) => return,
+        };
+
+        let image = DynamicImage::from(decoded).resize_exact(
+            width.into(),
+            height.into(),
+            image::imageops::FilterType::Nearest,
+        );
+
+        self.text = dither_image(image, settings, None).to_string();
+    }
+}
@@ -0,0 +1,117 @@
+use image::{imageops, DynamicImage, GenericImage, GenericImageView, Rgba};
+
+/// How a masked region is hidden
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MaskMode {
+    Blank,
+    Blur,
+}
+
+impl MaskMode {
+    pub fn from_name(name: &str) -> Option<MaskMode> {
+        match name {
+            "blank" => Some(MaskMode::Blank),
+            "blur" => Some(MaskMode::Blur),
+            _ => None,
+        }
+    }
+}
+
+/// A region of the frame to hide, as fractions of the frame size
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Mask {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Mask {
+    /// Builds a mask from two opposite corners
+    pub fn from_corners(a: (f32, f32), b: (f32, f32)) -> Mask {
+        let (left, right) = (a.0.min(b.0).clamp(0.0, 1.0), a.0.max(b.0).clamp(0.0, 1.0));
+        let (top, bottom) = (a.1.min(b.1).clamp(0.0, 1.0), a.1.max(b.1).clamp(0.0, 1.0));
+
+        Mask {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+
+    /// Reads masks written as `x,y,width,height` fractions, separated by `;`
+    pub fn parse_list(text: &str) -> Result<Vec<Mask>, String> {
+        text.split(';')
+            .filter(|m| !m.trim().is_empty())
+            .map(|m| {
+                let values: Vec<f32> = m
+                    .split(',')
+                    .map(|v| v.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("Not a privacy region: {}", m.trim()))?;
+
+                match values[..] {
+                    [x, y, width, height] => {
+                        Ok(Mask::from_corners((x, y), (x + width, y + height)))
+                    }
+                    _ => Err(format!("Not a privacy region: {}", m.trim())),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The regions hidden from every frame before it is rendered
+#[derive(Clone, PartialEq, Debug)]
+pub struct Privacy {
+    pub masks: Vec<Mask>,
+    pub mode: MaskMode,
+}
+
+impl Default for Privacy {
+    fn default() -> Privacy {
+        Privacy {
+            masks: Vec::new(),
+            mode: MaskMode::Blank,
+        }
+    }
+}
+
+impl Privacy {
+    pub fn apply(&self, image: &mut DynamicImage) {
+        let (width, height) = image.dimensions();
+
+        for mask in &self.masks {
+            let x = (mask.x * width as f32) as u32;
+            let y = (mask.y * height as f32) as u32;
+            let w = ((mask.width * width as f32) as u32).min(width - x.min(width));
+            let h = ((mask.height * height as f32) as u32).min(height - y.min(height));
+            if w == 0 || h == 0 {
+                continue;
+            }
+
+            match self.mode {
+                MaskMode::Blank => {
+                    for py in y..y + h {
+                        for px in x..x + w {
+                            image.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+                        }
+                    }
+                }
+                MaskMode::Blur => {
+                    // shrinking the region to a few pixels and back smears out any detail
+                    let region = image.crop_imm(x, y, w, h);
+                    let small = region.resize_exact(
+                        (w / 16).max(1),
+                        (h / 16).max(1),
+                        imageops::FilterType::Triangle,
+                    );
+                    let blurred = small.resize_exact(w, h, imageops::FilterType::Triangle);
+                    // the region fits inside the image, so copying it back can not fail
+                    let _ = image.copy_from(&blurred, x, y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::{fs, path::Path, time::Instant};
+
+/// Scrolling speed by default, in cells per second
+pub const DEFAULT_SPEED: f32 = 8.0;
+
+/// Blank cells between the end of the text and its start when it loops
+const GAP: usize = 8;
+
+/// Text scrolling across the bottom of the view, right to left, like a teleprompter
+pub struct Prompter {
+    /// The lines of the file, joined into a single one
+    text: Vec<char>,
+    speed: f32,
+    started: Instant,
+}
+
+impl Prompter {
+    pub fn load(path: &Path, speed: f32) -> Result<Prompter, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let text: Vec<char> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("   ")
+            .chars()
+            .collect();
+        if text.is_empty() {
+            return Err(format!("{}: the file is empty", path.display()));
+        }
+
+        Ok(Prompter {
+            text,
+            speed,
+            started: Instant::now(),
+        })
+    }
+
+    /// The part of the text visible in a row of the given width, entering from the right
+    pub fn window(&self, width: usize) -> String {
+        // the text starts just off the right edge, then loops with a gap
+        let period = self.text.len() + GAP;
+        let scrolled = (self.started.elapsed().as_secs_f32() * self.speed) as usize;
+
+        (0..width)
+            .map(|x| {
+                (x + scrolled)
+                    .checked_sub(width)
+                    .map(|i| i % period)
+                    .and_then(|i| self.text.get(i).copied())
+                    .unwrap_or(' ')
+            })
+            .collect()
+    }
+}
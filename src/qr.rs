@@ -0,0 +1,61 @@
+use image::{DynamicImage, GenericImageView};
+
+/// Frames are downscaled to this width before scanning, which is still plenty
+/// for a code held up to the camera
+const SCAN_WIDTH: u32 = 640;
+
+/// A decoded code, with its corners as fractions of the image size
+pub struct QrCode {
+    pub text: String,
+    pub corners: [(f32, f32); 4],
+}
+
+/// Looks for QR codes in the frames
+#[derive(Default)]
+pub struct QrScanner {
+    /// The code found in the last rendered frame
+    pub found: Option<QrCode>,
+    /// Text of the last code shown to the user, so that it is not shown again
+    /// on every frame it stays in view
+    shown: Option<String>,
+}
+
+impl QrScanner {
+    /// Scans an image, mirroring the corners when it is displayed mirrored since
+    /// a mirrored code can not be decoded
+    pub fn scan(&mut self, image: &DynamicImage, mirror: bool) {
+        let image = if image.width() > SCAN_WIDTH {
+            image.thumbnail(SCAN_WIDTH, u32::MAX)
+        } else {
+            image.clone()
+        };
+        let gray = image.to_luma8();
+        let (width, height) = image.dimensions();
+
+        let mut prepared =
+            rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+                gray.get_pixel(x as u32, y as u32)[0]
+            });
+
+        self.found = prepared.detect_grids().iter().find_map(|grid| {
+            let (_, text) = grid.decode().ok()?;
+            let corners = grid.bounds.map(|p| {
+                let x = p.x as f32 / width as f32;
+                let y = p.y as f32 / height as f32;
+                (if mirror { 1.0 - x } else { x }, y)
+            });
+            Some(QrCode { text, corners })
+        });
+    }
+
+    /// Returns the text of a code found since the last call, once per code
+    pub fn take_new(&mut self) -> Option<String> {
+        let text = &self.found.as_ref()?.text;
+        if self.shown.as_ref() == Some(text) {
+            return None;
+        }
+
+        self.shown = Some(text.clone());
+        Some(text.clone())
+    }
+}
@@ -0,0 +1,235 @@
+use crate::blue_noise::{self, SIZE};
+use crate::settings::DitherAlgorithm;
+
+/// Turns the positions of the pixels on the ramp, fractional indices from 0
+/// to `last`, into the index of the character each one gets. The pixels are
+/// given row after row, `width` to a row.
+pub trait Quantizer {
+    fn quantize(&self, values: &[f32], width: usize, last: f32) -> Vec<u8>;
+}
+
+/// The quantizer behind a dithering algorithm
+pub fn quantizer(algorithm: DitherAlgorithm) -> Box<dyn Quantizer> {
+    match algorithm {
+        DitherAlgorithm::Threshold => Box::new(Threshold),
+        DitherAlgorithm::FloydSteinberg => Box::new(ErrorDiffusion(&FLOYD_STEINBERG)),
+        DitherAlgorithm::Atkinson => Box::new(ErrorDiffusion(&ATKINSON)),
+        DitherAlgorithm::SierraLite => Box::new(ErrorDiffusion(&SIERRA_LITE)),
+        DitherAlgorithm::JarvisJudiceNinke => Box::new(ErrorDiffusion(&JARVIS_JUDICE_NINKE)),
+        DitherAlgorithm::Ordered(size) => Box::new(Ordered(size)),
+        DitherAlgorithm::BlueNoise => Box::new(BlueNoise),
+    }
+}
+
+/// Rounds every pixel to the closest character
+pub struct Threshold;
+
+impl Quantizer for Threshold {
+    fn quantize(&self, values: &[f32], _width: usize, last: f32) -> Vec<u8> {
+        values
+            .iter()
+            .map(|v| v.round().clamp(0.0, last) as u8)
+            .collect()
+    }
+}
+
+/// Where the error of a pixel goes, as offsets to the right and down and the
+/// share each neighbor gets
+type Kernel = [(i32, i32, f32)];
+
+const FLOYD_STEINBERG: [(i32, i32, f32); 4] = [
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+
+/// Passes on only three quarters of the error, which keeps more contrast
+/// at the cost of the darkest and lightest detail
+const ATKINSON: [(i32, i32, f32); 6] = [
+    (1, 0, 1.0 / 8.0),
+    (2, 0, 1.0 / 8.0),
+    (-1, 1, 1.0 / 8.0),
+    (0, 1, 1.0 / 8.0),
+    (1, 1, 1.0 / 8.0),
+    (0, 2, 1.0 / 8.0),
+];
+
+const SIERRA_LITE: [(i32, i32, f32); 3] =
+    [(1, 0, 2.0 / 4.0), (-1, 1, 1.0 / 4.0), (0, 1, 1.0 / 4.0)];
+
+/// Spreads the error over two rows, the smoothest and the slowest of them
+const JARVIS_JUDICE_NINKE: [(i32, i32, f32); 12] = [
+    (1, 0, 7.0 / 48.0),
+    (2, 0, 5.0 / 48.0),
+    (-2, 1, 3.0 / 48.0),
+    (-1, 1, 5.0 / 48.0),
+    (0, 1, 7.0 / 48.0),
+    (1, 1, 5.0 / 48.0),
+    (2, 1, 3.0 / 48.0),
+    (-2, 2, 1.0 / 48.0),
+    (-1, 2, 3.0 / 48.0),
+    (0, 2, 5.0 / 48.0),
+    (1, 2, 3.0 / 48.0),
+    (2, 2, 1.0 / 48.0),
+];
+
+/// Quantizes one pixel at a time from the top left, passing what rounding
+/// lost on to the neighbors not yet visited
+pub struct ErrorDiffusion(&'static Kernel);
+
+impl Quantizer for ErrorDiffusion {
+    fn quantize(&self, values: &[f32], width: usize, last: f32) -> Vec<u8> {
+        let mut values = values.to_vec();
+        let height = values.len() / width.max(1);
+        let mut levels = vec![0; values.len()];
+
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                let level = values[i].round().clamp(0.0, last);
+                let error = values[i] - level;
+                levels[i] = level as u8;
+
+                for &(dx, dy, share) in self.0 {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx >= 0 && (nx as usize) < width && (ny as usize) < height {
+                        values[ny as usize * width + nx as usize] += error * share;
+                    }
+                }
+            }
+        }
+        levels
+    }
+}
+
+/// Offsets every pixel by its entry in a Bayer matrix of the given size
+pub struct Ordered(u32);
+
+impl Quantizer for Ordered {
+    fn quantize(&self, values: &[f32], width: usize, last: f32) -> Vec<u8> {
+        let size = self.0;
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let (x, y) = ((i % width) as u32, (i / width) as u32);
+                let threshold =
+                    (bayer(x % size, y % size, size) as f32 + 0.5) / (size * size) as f32 - 0.5;
+                (value + threshold).round().clamp(0.0, last) as u8
+            })
+            .collect()
+    }
+}
+
+/// The entry of a Bayer matrix, from 0 to size² - 1, built up from the 2x2
+/// one by placing four copies of the smaller matrix in its pattern
+fn bayer(x: u32, y: u32, size: u32) -> u32 {
+    if size <= 1 {
+        return 0;
+    }
+    let half = size / 2;
+    let quadrant = [0, 2, 3, 1][((y / half) * 2 + x / half) as usize];
+    4 * bayer(x % half, y % half, half) + quadrant
+}
+
+/// Offsets every pixel by its entry in a blue-noise texture, a pattern as
+/// stable as the Bayer matrices but without their grid
+pub struct BlueNoise;
+
+impl Quantizer for BlueNoise {
+    fn quantize(&self, values: &[f32], width: usize, last: f32) -> Vec<u8> {
+        let texture = blue_noise::texture();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let (x, y) = (i % width % SIZE, i / width % SIZE);
+                let threshold = (texture[y * SIZE + x] as f32 + 0.5) / texture.len() as f32 - 0.5;
+                (value + threshold).round().clamp(0.0, last) as u8
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KERNELS: [(&str, &Kernel, f32); 4] = [
+        ("Floyd-Steinberg", &FLOYD_STEINBERG, 1.0),
+        ("Atkinson", &ATKINSON, 0.75),
+        ("Sierra Lite", &SIERRA_LITE, 1.0),
+        ("Jarvis-Judice-Ninke", &JARVIS_JUDICE_NINKE, 1.0),
+    ];
+
+    #[test]
+    fn kernels_pass_on_their_share_of_the_error_forward() {
+        for (name, kernel, total) in KERNELS {
+            let sum: f32 = kernel.iter().map(|(_, _, share)| share).sum();
+            assert!((sum - total).abs() < 1e-6, "{}", name);
+            // only to pixels not visited yet
+            assert!(
+                kernel.iter().all(|&(dx, dy, _)| dy > 0 || dx > 0),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn a_flat_half_splits_between_two_levels() {
+        for (name, kernel, _) in KERNELS {
+            let levels = ErrorDiffusion(kernel).quantize(&[0.5; 32 * 32], 32, 1.0);
+            let lit = levels.iter().filter(|&&l| l == 1).count() as f32 / levels.len() as f32;
+            assert!(levels.iter().all(|&l| l <= 1), "{}", name);
+            assert!((lit - 0.5).abs() < 0.05, "{}: {}", name, lit);
+        }
+    }
+
+    #[test]
+    fn the_mean_is_kept_between_levels() {
+        // a quarter of the way from level 2 to level 3
+        let levels = ErrorDiffusion(&FLOYD_STEINBERG).quantize(&[2.25; 16 * 16], 16, 9.0);
+        let mean = levels.iter().map(|&l| l as f32).sum::<f32>() / levels.len() as f32;
+        assert!(levels.iter().all(|&l| l == 2 || l == 3));
+        assert!((mean - 2.25).abs() < 0.05, "{}", mean);
+    }
+
+    #[test]
+    fn single_rows_and_columns_do_not_panic() {
+        let values: Vec<f32> = (0..10).map(|i| i as f32 / 3.0).collect();
+        for (_, kernel, _) in KERNELS {
+            assert_eq!(ErrorDiffusion(kernel).quantize(&values, 10, 3.0).len(), 10);
+            assert_eq!(ErrorDiffusion(kernel).quantize(&values, 1, 3.0).len(), 10);
+            assert!(ErrorDiffusion(kernel).quantize(&[], 0, 3.0).is_empty());
+        }
+    }
+
+    /// Every entry of a `size`x`size` matrix, row after row
+    fn matrix(size: u32) -> Vec<u32> {
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| bayer(x, y, size)))
+            .collect()
+    }
+
+    #[test]
+    fn bayer_matrices_hold_every_rank_once() {
+        for size in [2, 4, 8] {
+            let mut entries = matrix(size);
+            entries.sort_unstable();
+            assert_eq!(entries, (0..size * size).collect::<Vec<_>>(), "{}", size);
+        }
+    }
+
+    #[test]
+    fn the_smallest_bayer_matrix_crosses_over() {
+        assert_eq!(matrix(2), vec![0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn ordered_dithering_keeps_the_mean() {
+        let levels = Ordered(4).quantize(&[0.25; 16], 4, 1.0);
+        assert_eq!(levels.iter().filter(|&&l| l == 1).count(), 4);
+    }
+}
@@ -0,0 +1,222 @@
+use image::{Rgb, RgbImage};
+use tui::style::Color;
+
+use crate::canvas::{Canvas, Cell};
+use crate::color::xterm_rgb;
+
+/// Size of a cell in pixels, twice as tall as wide like a terminal cell
+pub const CELL_WIDTH: u32 = 6;
+pub const CELL_HEIGHT: u32 = 12;
+
+/// Rows left blank above a glyph, which centers the 7 rows of the font
+const GLYPH_TOP: u32 = 2;
+
+/// Colors of cells that do not set their own, as on a dark terminal
+const DEFAULT_FG: [u8; 3] = [224, 224, 224];
+const DEFAULT_BG: [u8; 3] = [0, 0, 0];
+
+/// The classic 5x7 font of character LCDs for the printable ASCII range, one
+/// byte per column from left to right, whose lowest bit is the top row
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x14, 0x08, 0x3E, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+/// Draws the glyphs of a canvas as an image, the way a terminal would show them
+pub fn rasterize(canvas: &Canvas) -> RgbImage {
+    let mut image = RgbImage::new(
+        canvas.width as u32 * CELL_WIDTH,
+        canvas.height as u32 * CELL_HEIGHT,
+    );
+
+    for y in 0..canvas.height {
+        for (x, cell) in canvas.row(y).iter().enumerate() {
+            draw_cell(
+                &mut image,
+                x as u32 * CELL_WIDTH,
+                y as u32 * CELL_HEIGHT,
+                cell,
+            );
+        }
+    }
+    image
+}
+
+fn draw_cell(image: &mut RgbImage, left: u32, top: u32, cell: &Cell) {
+    let fg = Rgb(cell.fg.map_or(DEFAULT_FG, rgb));
+    let bg = Rgb(cell.bg.map_or(DEFAULT_BG, rgb));
+
+    for y in 0..CELL_HEIGHT {
+        for x in 0..CELL_WIDTH {
+            let color = if lit(cell.ch, x, y) { fg } else { bg };
+            image.put_pixel(left + x, top + y, color);
+        }
+    }
+}
+
+/// How much of a cell the glyph covers, from 0 to 1, as the images draw it,
+/// `None` for the characters they only show as a question mark
+pub fn coverage(ch: char) -> Option<f32> {
+    let drawn =
+        matches!(ch, ' '..='~' | '█' | '▀' | '▄' | '░' | '▒' | '▓' | '\u{2800}'..='\u{28FF}');
+    if !drawn {
+        return None;
+    }
+    let pixels = (0..CELL_HEIGHT)
+        .flat_map(|y| (0..CELL_WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| lit(ch, x, y))
+        .count();
+    Some(pixels as f32 / (CELL_WIDTH * CELL_HEIGHT) as f32)
+}
+
+/// Whether the glyph lights the pixel of its cell
+fn lit(ch: char, x: u32, y: u32) -> bool {
+    // the block elements fill the cell, or a half of it, rather than being
+    // drawn, and the shades a quarter, a half or three quarters of its pixels
+    match ch {
+        '█' => true,
+        '▀' => y < CELL_HEIGHT / 2,
+        '▄' => y >= CELL_HEIGHT / 2,
+        '░' => x.is_multiple_of(2) && y.is_multiple_of(2),
+        '▒' => (x + y).is_multiple_of(2),
+        '▓' => x.is_multiple_of(2) || y.is_multiple_of(2),
+        // braille dots are 2x2 pixels in a 3x3 square each, numbered down the
+        // left column then the right one, with the bottom row last
+        ch @ '\u{2800}'..='\u{28FF}' => {
+            let bits = ch as u32 - 0x2800;
+            let (column, row) = (x / 3, y / 3);
+            let bit = match row {
+                3 => 6 + column,
+                _ => row + column * 3,
+            };
+            x % 3 < 2 && y % 3 < 2 && bits >> bit & 1 == 1
+        }
+        ch => {
+            // glyphs outside the font show as a question mark
+            let glyph = match ch {
+                ' '..='~' => FONT[ch as usize - ' ' as usize],
+                _ => FONT['?' as usize - ' ' as usize],
+            };
+            let row = y.wrapping_sub(GLYPH_TOP);
+            x < 5 && row < 7 && glyph[x as usize] >> row & 1 == 1
+        }
+    }
+}
+
+/// The RGB value of a terminal color, with the standard VGA values for the named ones
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::Red => [170, 0, 0],
+        Color::Green => [0, 170, 0],
+        Color::Yellow => [170, 85, 0],
+        Color::Blue => [0, 0, 170],
+        Color::Magenta => [170, 0, 170],
+        Color::Cyan => [0, 170, 170],
+        Color::Gray => [170, 170, 170],
+        Color::DarkGray => [85, 85, 85],
+        Color::LightRed => [255, 85, 85],
+        Color::LightGreen => [85, 255, 85],
+        Color::LightYellow => [255, 255, 85],
+        Color::LightBlue => [85, 85, 255],
+        Color::LightMagenta => [255, 85, 255],
+        Color::LightCyan => [85, 255, 255],
+        Color::White => [255, 255, 255],
+        Color::Indexed(index) => xterm_rgb(index),
+        Color::Reset => DEFAULT_FG,
+    }
+}
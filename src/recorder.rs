@@ -0,0 +1,234 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::asciivid;
+use crate::canvas::Canvas;
+use crate::raster::rasterize;
+use crate::settings::Settings;
+
+/// What a recording is saved as
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordFormat {
+    /// A single text file of the glyphs
+    Text,
+    /// A directory of numbered PNG images of the glyphs, along with a timing
+    /// file, for assembling a video with other tools
+    Png,
+    /// A compressed `.asciivid` file of the glyphs and the look they were
+    /// recorded with, which the viewer can play back
+    AsciiVid,
+}
+
+impl RecordFormat {
+    pub fn from_name(name: &str) -> Option<RecordFormat> {
+        match name {
+            "text" => Some(RecordFormat::Text),
+            "png" => Some(RecordFormat::Png),
+            "asciivid" => Some(RecordFormat::AsciiVid),
+            _ => None,
+        }
+    }
+}
+
+/// Frames waiting for the PNG encoder before the viewer has to wait for it
+const PNG_QUEUE: usize = 8;
+
+/// Where the frames of a recording go, in each of the formats
+enum Output {
+    Text(BufWriter<File>),
+    /// The timing file next to the images, and what saves them
+    Png(BufWriter<File>, PngEncoder),
+    AsciiVid(asciivid::Writer),
+}
+
+/// Rasterizes and saves the images of a recording on a thread of its own,
+/// since encoding a PNG takes longer than a frame of the viewer
+struct PngEncoder {
+    frames: SyncSender<(PathBuf, Canvas)>,
+    /// Stops at the first image that cannot be saved, with why
+    worker: JoinHandle<io::Result<()>>,
+    /// Size of the images saved so far
+    bytes: Arc<AtomicU64>,
+}
+
+impl PngEncoder {
+    fn start() -> PngEncoder {
+        let (frames, queue) = mpsc::sync_channel::<(PathBuf, Canvas)>(PNG_QUEUE);
+        let bytes = Arc::new(AtomicU64::new(0));
+        let saved = Arc::clone(&bytes);
+
+        let worker = thread::spawn(move || {
+            for (path, canvas) in queue {
+                rasterize(&canvas)
+                    .save(&path)
+                    .map_err(|e| io::Error::other(e.to_string()))?;
+                saved.fetch_add(fs::metadata(&path)?.len(), Ordering::Relaxed);
+            }
+            Ok(())
+        });
+
+        PngEncoder {
+            frames,
+            worker,
+            bytes,
+        }
+    }
+
+    fn encode(&self, path: PathBuf, canvas: &Canvas) -> io::Result<()> {
+        // the worker only hangs up after an error, which finishing reports
+        self.frames
+            .send((path, canvas.clone()))
+            .map_err(|_| io::Error::other("could not save an image"))
+    }
+
+    /// Waits for the queued images to be saved
+    fn finish(self) -> io::Result<()> {
+        drop(self.frames);
+        self.worker
+            .join()
+            .map_err(|_| io::Error::other("the PNG encoder crashed"))?
+    }
+}
+
+/// Writes every frame shown in the viewer to a text file, each one preceded
+/// by a line giving its number and time since the recording started, or to
+/// numbered PNG images whose times are listed in `timing.txt`, or to a
+/// compact recording
+pub struct Recorder {
+    /// The file, or the directory of the images
+    pub path: PathBuf,
+    output: Output,
+    started: Instant,
+    frames: u64,
+    /// Bytes written so far, which is the size of the finished file
+    pub bytes: u64,
+}
+
+impl Recorder {
+    pub fn start(dir: &Path, format: RecordFormat, settings: &Settings) -> io::Result<Recorder> {
+        fs::create_dir_all(dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let (path, output) = match format {
+            RecordFormat::Text => {
+                let path = dir.join(format!("recording-{}.txt", millis));
+                let file = File::create(&path)?;
+                (path, Output::Text(BufWriter::new(file)))
+            }
+            RecordFormat::Png => {
+                let path = dir.join(format!("recording-{}", millis));
+                fs::create_dir(&path)?;
+                let file = File::create(path.join("timing.txt"))?;
+                (path, Output::Png(BufWriter::new(file), PngEncoder::start()))
+            }
+            RecordFormat::AsciiVid => {
+                let path = dir.join(format!("recording-{}.{}", millis, asciivid::EXTENSION));
+                let encoder = asciivid::create(&path, settings)?;
+                (path, Output::AsciiVid(encoder))
+            }
+        };
+
+        Ok(Recorder {
+            output,
+            path,
+            started: Instant::now(),
+            frames: 0,
+            bytes: 0,
+        })
+    }
+
+    pub fn write(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let time = self.elapsed();
+        match &mut self.output {
+            Output::Text(file) => {
+                let frame = format!(
+                    "--- frame {} at {}ms ---\n{}",
+                    self.frames,
+                    time.as_millis(),
+                    canvas
+                );
+                file.write_all(frame.as_bytes())?;
+                self.bytes += frame.len() as u64;
+            }
+            Output::Png(timing, encoder) => {
+                // one line per image: its name and when it was shown
+                let name = format!("frame-{:06}.png", self.frames);
+                encoder.encode(self.path.join(&name), canvas)?;
+                writeln!(timing, "{} {}", name, time.as_millis())?;
+                self.bytes = encoder.bytes.load(Ordering::Relaxed);
+            }
+            Output::AsciiVid(encoder) => {
+                asciivid::write_frame(encoder, time, canvas)?;
+                // what has been compressed so far, the rest is still buffered
+                self.bytes = fs::metadata(&self.path)?.len();
+            }
+        }
+
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn finish(self) -> io::Result<PathBuf> {
+        match self.output {
+            Output::Text(mut file) => file.flush()?,
+            Output::Png(mut timing, encoder) => {
+                timing.flush()?;
+                encoder.finish()?;
+            }
+            // a compact recording is only complete once its stream is ended,
+            // which writes the last of the compressed frames
+            Output::AsciiVid(encoder) => encoder.finish()?.flush()?,
+        }
+        Ok(self.path)
+    }
+
+    /// Stops recording and deletes what was written so far
+    pub fn discard(self) -> io::Result<()> {
+        match self.output {
+            Output::Png(timing, encoder) => {
+                drop(timing);
+                // the images still queued would be saved into a removed directory
+                let _ = encoder.finish();
+                fs::remove_dir_all(&self.path)
+            }
+            output => {
+                drop(output);
+                fs::remove_file(&self.path)
+            }
+        }
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MB`
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
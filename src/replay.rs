@@ -0,0 +1,70 @@
+use image::imageops;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::source::RgbFrame;
+
+/// How far back the viewer can be rewound
+const REPLAY_DURATION: Duration = Duration::from_secs(5);
+
+/// Frames are downscaled to this width to keep the buffer's memory in check,
+/// which is still far more than a terminal can show
+const MAX_WIDTH: u32 = 640;
+
+/// The last few seconds of frames, to scrub through while paused
+#[derive(Default)]
+pub struct ReplayBuffer {
+    frames: VecDeque<(Instant, RgbFrame)>,
+    /// Index of the frame being looked at, `None` when following the camera
+    position: Option<usize>,
+}
+
+impl ReplayBuffer {
+    pub fn push(&mut self, frame: &RgbFrame) {
+        let frame = if frame.width() > MAX_WIDTH {
+            let height = frame.height() * MAX_WIDTH / frame.width();
+            imageops::thumbnail(frame, MAX_WIDTH, height.max(1))
+        } else {
+            frame.clone()
+        };
+
+        let now = Instant::now();
+        self.frames.push_back((now, frame));
+        while let Some((captured, _)) = self.frames.front() {
+            if now.duration_since(*captured) <= REPLAY_DURATION {
+                break;
+            }
+            self.frames.pop_front();
+        }
+
+        self.position = None;
+    }
+
+    /// Moves `step` frames backward or forward and returns the frame there
+    pub fn step(&mut self, step: i32) -> Option<RgbFrame> {
+        if self.frames.is_empty() {
+            return None;
+        }
+
+        let last = self.frames.len() as i32 - 1;
+        let current = self.position.map(|p| p as i32).unwrap_or(last);
+        let position = (current + step).clamp(0, last) as usize;
+        self.position = Some(position);
+
+        Some(self.frames[position].1.clone())
+    }
+
+    /// How long before the newest frame the current one was captured
+    pub fn offset(&self) -> Option<Duration> {
+        let position = self.position?;
+        let (newest, _) = self.frames.back()?;
+
+        Some(newest.duration_since(self.frames[position].0))
+    }
+
+    pub fn clear(&mut self) {
+        *self = ReplayBuffer::default();
+    }
+}
@@ -0,0 +1,59 @@
+use image::{imageops::FilterType, DynamicImage, RgbImage};
+
+use crate::settings::ResizeFilter;
+
+/// Scales the image to the size of the view, one pixel per cell
+pub fn resize(image: &DynamicImage, width: u32, height: u32, filter: ResizeFilter) -> DynamicImage {
+    let filter = match filter {
+        ResizeFilter::Box => return area_average(image, width, height),
+        ResizeFilter::Nearest => FilterType::Nearest,
+        ResizeFilter::Triangle => FilterType::Triangle,
+        ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+    };
+    image.resize_exact(width, height, filter)
+}
+
+/// Scales the image so that every output pixel is the average of all the
+/// source pixels it covers. Picking a single pixel per cell makes fine detail
+/// shimmer as it moves between the picked pixels from one frame to the next,
+/// which averaging the whole area does not.
+fn area_average(image: &DynamicImage, width: u32, height: u32) -> DynamicImage {
+    let source = image.to_rgb8();
+    let (source_width, source_height) = source.dimensions();
+    if source_width == 0 || source_height == 0 {
+        return DynamicImage::ImageRgb8(RgbImage::new(width, height));
+    }
+
+    // the source columns and rows each output pixel covers, at least one
+    // when the image is made larger
+    let span = |i: u32, size: u32, source_size: u32| {
+        let start = (i as u64 * source_size as u64 / size as u64) as u32;
+        let end = ((i as u64 + 1) * source_size as u64 / size as u64) as u32;
+        (start, end.max(start + 1))
+    };
+    let columns: Vec<(u32, u32)> = (0..width).map(|x| span(x, width, source_width)).collect();
+
+    let mut output = RgbImage::new(width, height);
+    for y in 0..height {
+        let (top, bottom) = span(y, height, source_height);
+        for (x, &(left, right)) in columns.iter().enumerate() {
+            let mut sum = [0u32; 3];
+            for sy in top..bottom {
+                for sx in left..right {
+                    let pixel = source.get_pixel(sx, sy);
+                    for c in 0..3 {
+                        sum[c] += pixel[c] as u32;
+                    }
+                }
+            }
+
+            let count = (bottom - top) * (right - left);
+            let pixel = output.get_pixel_mut(x as u32, y);
+            for c in 0..3 {
+                pixel[c] = ((sum[c] + count / 2) / count) as u8;
+            }
+        }
+    }
+
+    DynamicImage::ImageRgb8(output)
+}
@@ -0,0 +1,149 @@
+use image::{imageops::FilterType, DynamicImage, Rgb};
+use tui::{layout::Rect, style::Color};
+
+use crate::canvas::{Canvas, Cell};
+use crate::dither::adjust;
+use crate::effects::crt;
+use crate::settings::Settings;
+
+/// Width of a retro pixel in cells, and its height in half cells, which keeps it square
+const PIXEL_SIZE: u32 = 3;
+
+/// How far the ordered dithering nudges a channel, either way, before picking the palette color
+const SPREAD: f32 = 48.0;
+
+/// The 4x4 Bayer matrix, the thresholds of the ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+const GAME_BOY: [[u8; 3]; 4] = [[15, 56, 15], [48, 98, 48], [139, 172, 15], [155, 188, 15]];
+
+/// Palette 1 at high intensity
+const CGA: [[u8; 3]; 4] = [[0, 0, 0], [85, 255, 255], [255, 85, 255], [255, 255, 255]];
+
+/// A selection of the colors of the NES picture processing unit
+const NES: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [124, 124, 124],
+    [188, 188, 188],
+    [252, 252, 252],
+    [168, 16, 0],
+    [248, 56, 0],
+    [252, 160, 68],
+    [172, 124, 0],
+    [248, 184, 0],
+    [0, 120, 0],
+    [0, 184, 0],
+    [88, 216, 84],
+    [0, 0, 188],
+    [0, 120, 248],
+    [60, 188, 252],
+    [216, 0, 204],
+];
+
+/// The look of an old console, in its few colors and with chunky pixels
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Palette {
+    GameBoy,
+    Cga,
+    Nes,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::GameBoy, Palette::Cga, Palette::Nes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::GameBoy => "game boy",
+            Palette::Cga => "cga",
+            Palette::Nes => "nes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Palette> {
+        Palette::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    /// The next palette, going through none after the last one
+    pub fn next(palette: Option<Palette>) -> Option<Palette> {
+        match palette {
+            None => Some(Palette::GameBoy),
+            Some(Palette::GameBoy) => Some(Palette::Cga),
+            Some(Palette::Cga) => Some(Palette::Nes),
+            Some(Palette::Nes) => None,
+        }
+    }
+
+    fn colors(self) -> &'static [[u8; 3]] {
+        match self {
+            Palette::GameBoy => &GAME_BOY,
+            Palette::Cga => &CGA,
+            Palette::Nes => &NES,
+        }
+    }
+
+    fn closest(self, rgb: [f32; 3]) -> [u8; 3] {
+        let distance = |color: &&[u8; 3]| -> f32 {
+            (0..3)
+                .map(|i| (color[i] as f32 - rgb[i]).powi(2))
+                .sum::<f32>()
+        };
+        *self
+            .colors()
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(&[0, 0, 0])
+    }
+}
+
+/// Draws the image in the colors of the palette instead of glyphs, a retro
+/// pixel at a time, with half blocks where a cell straddles two rows of them
+pub fn render(image: &DynamicImage, view: Rect, palette: Palette, settings: &Settings) -> Canvas {
+    let (width, height) = (view.width as u32, view.height as u32 * 2);
+    let pixels = image
+        .resize_exact(
+            width.div_ceil(PIXEL_SIZE).max(1),
+            height.div_ceil(PIXEL_SIZE).max(1),
+            FilterType::Triangle,
+        )
+        .to_rgb8();
+
+    let color = |x: u32, y: u32| {
+        let (px, py) = (x / PIXEL_SIZE, y / PIXEL_SIZE);
+        let Rgb(rgb) = *pixels.get_pixel(px, py);
+
+        let threshold = BAYER[(py % 4) as usize][(px % 4) as usize] as f32 / 16.0 - 0.5;
+        // the CRT rows are the half cells, so the scanlines come between the retro pixels too
+        let shade = if settings.crt {
+            crt(x, y, width, height)
+        } else {
+            1.0
+        };
+        let rgb =
+            rgb.map(|c| adjust(c as f32 / 255.0, settings) * shade * 255.0 + threshold * SPREAD);
+        let [r, g, b] = palette.closest(rgb);
+        Color::Rgb(r, g, b)
+    };
+
+    let mut canvas = Canvas::new(view.width as usize, view.height as usize);
+    for y in 0..view.height as u32 {
+        for x in 0..width {
+            let top = color(x, y * 2);
+            let bottom = color(x, y * 2 + 1);
+            let cell = if top == bottom {
+                Cell {
+                    ch: '█',
+                    fg: Some(top),
+                    bg: None,
+                }
+            } else {
+                Cell {
+                    ch: '▀',
+                    fg: Some(top),
+                    bg: Some(bottom),
+                }
+            };
+            canvas.set(x as usize, y as usize, cell);
+        }
+    }
+    canvas
+}
@@ -0,0 +1,767 @@
+use crate::effects::Effect;
+use crate::raster::coverage;
+use crate::retro::Palette;
+use std::time::Duration;
+
+/// Algorithm used to turn the grayscale frame into glyphs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DitherAlgorithm {
+    /// Maps every pixel straight to the closest character of the ramp
+    Threshold,
+    /// Spreads the error of every pixel over its neighbors to the right and
+    /// below, so that gradients come out as a mix of the characters around them
+    FloydSteinberg,
+    /// Error diffusion that lets a quarter of the error go, for more contrast
+    Atkinson,
+    /// A cheaper error diffusion over fewer neighbors
+    SierraLite,
+    /// Error diffusion over two rows of neighbors, the smoothest
+    JarvisJudiceNinke,
+    /// Offsets every pixel by its entry in a Bayer matrix of the given size,
+    /// which gives the same pattern from one frame to the next
+    Ordered(u32),
+    /// Like ordered dithering, with a blue-noise texture in place of the
+    /// matrix, which looks more like film grain
+    BlueNoise,
+}
+
+impl DitherAlgorithm {
+    pub const ALL: [DitherAlgorithm; 9] = [
+        DitherAlgorithm::Threshold,
+        DitherAlgorithm::FloydSteinberg,
+        DitherAlgorithm::Atkinson,
+        DitherAlgorithm::SierraLite,
+        DitherAlgorithm::JarvisJudiceNinke,
+        DitherAlgorithm::Ordered(2),
+        DitherAlgorithm::Ordered(4),
+        DitherAlgorithm::Ordered(8),
+        DitherAlgorithm::BlueNoise,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DitherAlgorithm::Threshold => "threshold",
+            DitherAlgorithm::FloydSteinberg => "floyd-steinberg",
+            DitherAlgorithm::Atkinson => "atkinson",
+            DitherAlgorithm::SierraLite => "sierra-lite",
+            DitherAlgorithm::JarvisJudiceNinke => "jjn",
+            DitherAlgorithm::Ordered(2) => "ordered-2x2",
+            DitherAlgorithm::Ordered(4) => "ordered-4x4",
+            DitherAlgorithm::Ordered(_) => "ordered-8x8",
+            DitherAlgorithm::BlueNoise => "blue-noise",
+        }
+    }
+
+    /// The next size of the Bayer matrix, switching to ordered dithering
+    pub fn next_ordered(self) -> DitherAlgorithm {
+        match self {
+            DitherAlgorithm::Ordered(2) => DitherAlgorithm::Ordered(4),
+            DitherAlgorithm::Ordered(4) => DitherAlgorithm::Ordered(8),
+            _ => DitherAlgorithm::Ordered(2),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DitherAlgorithm> {
+        DitherAlgorithm::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+/// How the glyphs are colored in the viewer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    Mono,
+    /// Glyphs in the closest colors of the xterm 256-color palette, for
+    /// terminals without true color
+    Ansi256,
+    /// Glyphs in the 24-bit colors of the frame
+    Truecolor,
+    /// Upper half blocks in the true colors of the frame, two pixels to a
+    /// cell, instead of glyphs
+    HalfBlock,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 4] = [
+        ColorMode::Mono,
+        ColorMode::Ansi256,
+        ColorMode::Truecolor,
+        ColorMode::HalfBlock,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::Mono => "mono",
+            ColorMode::Ansi256 => "256",
+            ColorMode::Truecolor => "truecolor",
+            ColorMode::HalfBlock => "half-block",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        ColorMode::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// How the luma of a frame is brought to the full range of the ramp
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Exposure {
+    /// Stretches the luma of every frame to the ends of the ramp, letting the
+    /// clipped share of the darkest and brightest pixels saturate
+    Normalize,
+    /// Applies a gain that slowly follows the mean luma towards a target,
+    /// which flickers less than normalizing every frame
+    Auto,
+}
+
+impl Exposure {
+    pub const ALL: [Exposure; 2] = [Exposure::Normalize, Exposure::Auto];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Exposure::Normalize => "normalize",
+            Exposure::Auto => "auto",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Exposure> {
+        Exposure::ALL.into_iter().find(|e| e.name() == name)
+    }
+}
+
+/// Curve that compresses the highlights of a high-dynamic-range frame before it is quantized
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToneMap {
+    Off,
+    Reinhard,
+    /// The filmic curve of Uncharted 2, with a softer shoulder
+    Hable,
+}
+
+impl ToneMap {
+    pub const ALL: [ToneMap; 3] = [ToneMap::Off, ToneMap::Reinhard, ToneMap::Hable];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMap::Off => "off",
+            ToneMap::Reinhard => "reinhard",
+            ToneMap::Hable => "hable",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ToneMap> {
+        ToneMap::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// How the frame is scaled down to one pixel per cell
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResizeFilter {
+    /// Picks a single pixel per cell, the sharpest and cheapest but the most
+    /// prone to shimmer
+    Nearest,
+    /// Averages every pixel a cell covers
+    Box,
+    Triangle,
+    /// The sharpest of the smooth filters, and the slowest
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    pub const ALL: [ResizeFilter; 4] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Box,
+        ResizeFilter::Triangle,
+        ResizeFilter::Lanczos3,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResizeFilter::Nearest => "nearest",
+            ResizeFilter::Box => "box",
+            ResizeFilter::Triangle => "triangle",
+            ResizeFilter::Lanczos3 => "lanczos3",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ResizeFilter> {
+        ResizeFilter::ALL.into_iter().find(|f| f.name() == name)
+    }
+}
+
+/// How much each of red, green and blue counts towards the luma the glyphs are picked from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LumaWeights {
+    /// The weights of HD video, which favor green the most
+    Bt709,
+    /// The weights of SD video, brighter on reds
+    Bt601,
+    Average,
+    /// Red, green and blue weights of the user's choosing
+    Custom([f32; 3]),
+}
+
+impl LumaWeights {
+    pub const ALL: [LumaWeights; 3] =
+        [LumaWeights::Bt709, LumaWeights::Bt601, LumaWeights::Average];
+
+    pub fn name(self) -> String {
+        match self {
+            LumaWeights::Bt709 => String::from("bt709"),
+            LumaWeights::Bt601 => String::from("bt601"),
+            LumaWeights::Average => String::from("average"),
+            LumaWeights::Custom([r, g, b]) => format!("{},{},{}", r, g, b),
+        }
+    }
+
+    /// One of the named weightings, or three comma-separated weights
+    pub fn from_name(name: &str) -> Option<LumaWeights> {
+        if let Some(weights) = LumaWeights::ALL.into_iter().find(|w| w.name() == name) {
+            return Some(weights);
+        }
+
+        let weights: Vec<f32> = name
+            .split(',')
+            .map(|w| w.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        match weights[..] {
+            [r, g, b] if r >= 0.0 && g >= 0.0 && b >= 0.0 && r + g + b > 0.0 => {
+                Some(LumaWeights::Custom([r, g, b]))
+            }
+            _ => None,
+        }
+    }
+
+    /// The weights, adding up to 1
+    pub fn weights(self) -> [f32; 3] {
+        match self {
+            LumaWeights::Bt709 => [0.2126, 0.7152, 0.0722],
+            LumaWeights::Bt601 => [0.299, 0.587, 0.114],
+            LumaWeights::Average => [1.0 / 3.0; 3],
+            LumaWeights::Custom([r, g, b]) => {
+                let sum = r + g + b;
+                [r / sum, g / sum, b / sum]
+            }
+        }
+    }
+}
+
+/// Frame rates the target can be set to
+pub const TARGET_FPS: [u32; 8] = [5, 10, 15, 24, 30, 60, 90, 120];
+
+/// The shade blocks, from empty to full
+const SHADE_RAMP: &str = " ░▒▓█";
+
+/// How bright the shade blocks look, from 0 to 1. They cover a quarter, a half
+/// and three quarters of the cell, but the eye sees coverage like light, so
+/// on the gamma-encoded scale of the luma they sit much higher than their
+/// share of the ramp.
+const SHADE_DENSITIES: [f32; 5] = [0.0, 0.53, 0.73, 0.88, 1.0];
+
+/// Built-in character ramps, ordered from dark to light
+pub const CHARSETS: [(&str, &str); 3] = [
+    ("standard", " .:-=+*#%@"),
+    (
+        "dense",
+        " .'`^\",:;Il!i><~+_-?][}{1)(|\\/tfjrxnuvczXYUJCLQ0OZmwqpdbkhao*#MW&8%B@$",
+    ),
+    ("shade", SHADE_RAMP),
+];
+
+/// Most characters a ramp can have, one for each level of a byte
+pub const MAX_RAMP: usize = 256;
+
+/// A named character ramp, ordered from dark to light
+#[derive(Clone, PartialEq, Debug)]
+pub struct Charset {
+    pub name: String,
+    pub ramp: Vec<char>,
+    /// How bright each character looks, when they are not evenly spaced
+    densities: Option<Vec<f32>>,
+}
+
+impl Charset {
+    pub fn new(name: &str, ramp: &str) -> Charset {
+        Charset {
+            name: name.to_string(),
+            ramp: ramp.chars().collect(),
+            densities: (ramp == SHADE_RAMP).then(|| SHADE_DENSITIES.to_vec()),
+        }
+    }
+
+    /// Checks a ramp typed by the user before making a charset of it
+    pub fn parse(name: &str, ramp: &str) -> Result<Charset, String> {
+        let len = ramp.chars().count();
+        if len < 2 {
+            return Err(String::from("The ramp needs at least two characters"));
+        }
+        // the levels a frame is quantized to are bytes
+        if len > MAX_RAMP {
+            return Err(format!(
+                "The ramp has {} characters, at most {} fit",
+                len, MAX_RAMP
+            ));
+        }
+        if let Some(c) = ramp.chars().find(|c| c.is_control()) {
+            return Err(format!("The ramp has a control character: {:?}", c));
+        }
+        // a character twice would stand for two levels, with one of them never
+        // told apart from the characters in between
+        if let Some(c) = ramp.chars().find(|c| ramp.matches(*c).count() > 1) {
+            return Err(format!("The ramp has {:?} more than once", c));
+        }
+        Ok(Charset::new(name, ramp))
+    }
+
+    pub fn builtin() -> Vec<Charset> {
+        CHARSETS
+            .iter()
+            .map(|(name, ramp)| Charset::new(name, ramp))
+            .collect()
+    }
+
+    /// The same characters from the one covering the least of a cell to the
+    /// one covering the most, `None` when some of them have no known coverage
+    pub fn sorted(&self) -> Option<Charset> {
+        let mut glyphs = self
+            .ramp
+            .iter()
+            .map(|&ch| coverage(ch).map(|c| (c, ch)))
+            .collect::<Option<Vec<(f32, char)>>>()?;
+        glyphs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let ramp: String = glyphs.into_iter().map(|(_, ch)| ch).collect();
+        Some(Charset::new(&self.name, &ramp))
+    }
+
+    /// Whether the ramp seems to be written from light to dark, its first
+    /// character covering more of a cell than its last
+    pub fn looks_reversed(&self) -> bool {
+        let ends = (self.ramp.first(), self.ramp.last());
+        match ends {
+            (Some(&first), Some(&last)) => match (coverage(first), coverage(last)) {
+                (Some(first), Some(last)) => first > last,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// The charset `step` places away from this one in the list, starting
+    /// over from the ends when this one is not in it, like a custom ramp
+    pub fn cycle(&self, charsets: &[Charset], step: i32) -> Charset {
+        let index = charsets
+            .iter()
+            .position(|c| c == self)
+            .map(|i| i as i32)
+            .unwrap_or(if step > 0 { -1 } else { 0 });
+        let index = (index + step).rem_euclid(charsets.len() as i32);
+        charsets[index as usize].clone()
+    }
+
+    /// Where a normalized luma falls on the ramp, as a fractional index, so
+    /// that rounding it picks the character that looks the closest
+    pub fn position(&self, value: f32) -> f32 {
+        let last = (self.ramp.len() - 1) as f32;
+        let densities = match self.densities.as_ref() {
+            Some(densities) => densities,
+            None => return value * last,
+        };
+
+        // interpolate between the two characters the value lies between
+        for (i, pair) in densities.windows(2).enumerate() {
+            if value <= pair[1] {
+                let span = (pair[1] - pair[0]).max(f32::EPSILON);
+                return i as f32 + ((value - pair[0]) / span).clamp(0.0, 1.0);
+            }
+        }
+        last
+    }
+}
+
+/// Every parameter that changes how a frame is rendered
+#[derive(Clone, PartialEq, Debug)]
+pub struct Settings {
+    /// Offset added to the normalized luma, in the range [-1, 1]
+    pub brightness: f32,
+    /// Factor applied around the mid-gray point
+    pub contrast: f32,
+    pub gamma: f32,
+    pub exposure: Exposure,
+    /// Percentage of the pixels left out at each end when normalizing, so
+    /// that a hot pixel or a dark corner does not flatten the contrast
+    pub clip: f32,
+    /// Equalizes regions of the frame separately (CLAHE), bringing out
+    /// subjects in front of a bright background
+    pub local_contrast: bool,
+    pub tone_map: ToneMap,
+    pub effect: Effect,
+    /// How much the frame is corrupted like a damaged signal, from 0 for not at all to 1
+    pub glitch: f32,
+    pub filter: ResizeFilter,
+    pub luma: LumaWeights,
+    pub algorithm: DitherAlgorithm,
+    pub charset: Charset,
+    /// Draws lines along the edges of the frame, in the direction they run,
+    /// and the ramp everywhere else
+    pub edges: bool,
+    pub color_mode: ColorMode,
+    /// Brightens the colors of the glyphs to their fullest, leaving how
+    /// bright a cell looks to the glyph alone
+    pub color_boost: bool,
+    /// Dims every other row and the edges, like an old CRT monitor
+    pub crt: bool,
+    /// Draws blocks in the colors of an old console instead of glyphs
+    pub retro: Option<Palette>,
+    /// Draws braille characters, 2x4 dots to a cell, instead of the ramp
+    pub braille: bool,
+    /// Flips the image horizontally, like looking in a mirror
+    pub mirror: bool,
+    /// Swaps the dark and light ends of the ramp
+    pub invert: bool,
+    /// How many times per second the viewer grabs and draws a frame
+    pub target_fps: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            exposure: Exposure::Normalize,
+            clip: 1.0,
+            local_contrast: false,
+            tone_map: ToneMap::Off,
+            effect: Effect::Off,
+            glitch: 0.0,
+            filter: ResizeFilter::Box,
+            luma: LumaWeights::Bt709,
+            algorithm: DitherAlgorithm::Threshold,
+            charset: Charset::new(CHARSETS[0].0, CHARSETS[0].1),
+            edges: false,
+            color_mode: ColorMode::Mono,
+            color_boost: false,
+            crt: false,
+            retro: None,
+            braille: false,
+            mirror: false,
+            invert: false,
+            target_fps: 60,
+        }
+    }
+}
+
+/// A row of the settings panel
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+    Brightness,
+    Contrast,
+    Gamma,
+    Exposure,
+    Clip,
+    LocalContrast,
+    ToneMap,
+    Effect,
+    Glitch,
+    Filter,
+    Luma,
+    Algorithm,
+    Charset,
+    Edges,
+    ColorMode,
+    ColorBoost,
+    Crt,
+    Retro,
+    Braille,
+    Mirror,
+    Invert,
+    TargetFps,
+    /// Not a value, but an action that restores the defaults
+    Reset,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 23] = [
+        Setting::Brightness,
+        Setting::Contrast,
+        Setting::Gamma,
+        Setting::Exposure,
+        Setting::Clip,
+        Setting::LocalContrast,
+        Setting::ToneMap,
+        Setting::Effect,
+        Setting::Glitch,
+        Setting::Filter,
+        Setting::Luma,
+        Setting::Algorithm,
+        Setting::Charset,
+        Setting::Edges,
+        Setting::ColorMode,
+        Setting::ColorBoost,
+        Setting::Crt,
+        Setting::Retro,
+        Setting::Braille,
+        Setting::Mirror,
+        Setting::Invert,
+        Setting::TargetFps,
+        Setting::Reset,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Brightness => "Brightness",
+            Setting::Contrast => "Contrast",
+            Setting::Gamma => "Gamma",
+            Setting::Exposure => "Exposure",
+            Setting::Clip => "Clip",
+            Setting::LocalContrast => "CLAHE",
+            Setting::ToneMap => "Tone map",
+            Setting::Effect => "Effect",
+            Setting::Glitch => "Glitch",
+            Setting::Filter => "Resize filter",
+            Setting::Luma => "Luma weights",
+            Setting::Algorithm => "Algorithm",
+            Setting::Charset => "Charset",
+            Setting::Edges => "Edges",
+            Setting::ColorMode => "Color mode",
+            Setting::ColorBoost => "Color boost",
+            Setting::Crt => "CRT",
+            Setting::Retro => "Retro",
+            Setting::Braille => "Braille",
+            Setting::Mirror => "Mirror",
+            Setting::Invert => "Invert",
+            Setting::TargetFps => "Target FPS",
+            Setting::Reset => "Reset to defaults",
+        }
+    }
+}
+
+impl Settings {
+    pub fn tick_rate(&self) -> Duration {
+        Duration::from_secs(1) / self.target_fps.max(1)
+    }
+
+    /// Formats the current value of a setting for display
+    pub fn value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Brightness => format!("{:+.2}", self.brightness),
+            Setting::Contrast => format!("{:.1}", self.contrast),
+            Setting::Gamma => format!("{:.1}", self.gamma),
+            Setting::Exposure => self.exposure.name().to_string(),
+            Setting::Clip => format!("{:.1}%", self.clip),
+            Setting::LocalContrast => on_off(self.local_contrast).to_string(),
+            Setting::ToneMap => self.tone_map.name().to_string(),
+            Setting::Effect => self.effect.name(),
+            Setting::Glitch if self.glitch == 0.0 => String::from("off"),
+            Setting::Glitch => format!("{:.0}%", self.glitch * 100.0),
+            Setting::Filter => self.filter.name().to_string(),
+            Setting::Luma => self.luma.name(),
+            Setting::Algorithm => self.algorithm.name().to_string(),
+            Setting::Charset => self.charset.name.clone(),
+            Setting::ColorMode => self.color_mode.name().to_string(),
+            Setting::Crt => on_off(self.crt).to_string(),
+            Setting::Retro => self.retro.map_or("off", Palette::name).to_string(),
+            Setting::Braille => on_off(self.braille).to_string(),
+            Setting::Edges => on_off(self.edges).to_string(),
+            Setting::ColorBoost => on_off(self.color_boost).to_string(),
+            Setting::Mirror => on_off(self.mirror).to_string(),
+            Setting::Invert => on_off(self.invert).to_string(),
+            Setting::TargetFps => self.target_fps.to_string(),
+            Setting::Reset => String::new(),
+        }
+    }
+
+    /// Moves a setting `step` increments up or down, cycling through the choices
+    pub fn adjust(&mut self, setting: Setting, step: i32) {
+        match setting {
+            Setting::Brightness => {
+                self.brightness = (self.brightness + 0.05 * step as f32).clamp(-1.0, 1.0)
+            }
+            Setting::Contrast => {
+                self.contrast = (self.contrast + 0.1 * step as f32).clamp(0.0, 3.0)
+            }
+            Setting::Gamma => self.gamma = (self.gamma + 0.1 * step as f32).clamp(0.1, 5.0),
+            Setting::Exposure => self.exposure = cycle(&Exposure::ALL, self.exposure, step),
+            Setting::Clip => self.clip = (self.clip + 0.5 * step as f32).clamp(0.0, 10.0),
+            Setting::LocalContrast => self.local_contrast = !self.local_contrast,
+            Setting::ToneMap => self.tone_map = cycle(&ToneMap::ALL, self.tone_map, step),
+            Setting::Effect => self.effect = cycle(&Effect::ALL, self.effect, step),
+            Setting::Glitch => {
+                // in tenths, so that stepping back down lands on exactly off
+                let tenths = (self.glitch * 10.0).round() as i32 + step;
+                self.glitch = tenths.clamp(0, 10) as f32 / 10.0
+            }
+            Setting::Filter => self.filter = cycle(&ResizeFilter::ALL, self.filter, step),
+            // custom weights are not in the list, cycling from them starts over
+            Setting::Luma => self.luma = cycle(&LumaWeights::ALL, self.luma, step),
+            Setting::Algorithm => {
+                self.algorithm = cycle(&DitherAlgorithm::ALL, self.algorithm, step)
+            }
+            // the app cycles it, since it knows the ramps of the config too
+            Setting::Charset => {}
+            Setting::ColorMode => self.color_mode = cycle(&ColorMode::ALL, self.color_mode, step),
+            Setting::Crt => self.crt = !self.crt,
+            Setting::Retro => {
+                let choices = [
+                    None,
+                    Some(Palette::GameBoy),
+                    Some(Palette::Cga),
+                    Some(Palette::Nes),
+                ];
+                self.retro = cycle(&choices, self.retro, step)
+            }
+            Setting::Braille => self.braille = !self.braille,
+            Setting::Edges => self.edges = !self.edges,
+            Setting::ColorBoost => self.color_boost = !self.color_boost,
+            Setting::Mirror => self.mirror = !self.mirror,
+            Setting::Invert => self.invert = !self.invert,
+            Setting::TargetFps => {
+                // stop at both ends rather than wrapping from 120 to 5, and
+                // step from the closest rate above one that is not in the list,
+                // such as one read from an old preset
+                let index = TARGET_FPS
+                    .iter()
+                    .position(|f| *f >= self.target_fps)
+                    .unwrap_or(TARGET_FPS.len() - 1);
+                let index = (index as i32 + step).clamp(0, TARGET_FPS.len() as i32 - 1);
+                self.target_fps = TARGET_FPS[index as usize];
+            }
+            // the app handles it, since it resets the viewport too
+            Setting::Reset => {}
+        }
+    }
+
+    /// Puts a single setting back to its default
+    pub fn restore(&mut self, setting: Setting) {
+        let defaults = Settings::default();
+        match setting {
+            Setting::Brightness => self.brightness = defaults.brightness,
+            Setting::Contrast => self.contrast = defaults.contrast,
+            Setting::Gamma => self.gamma = defaults.gamma,
+            Setting::Exposure => self.exposure = defaults.exposure,
+            Setting::Clip => self.clip = defaults.clip,
+            Setting::LocalContrast => self.local_contrast = defaults.local_contrast,
+            Setting::ToneMap => self.tone_map = defaults.tone_map,
+            Setting::Effect => self.effect = defaults.effect,
+            Setting::Glitch => self.glitch = defaults.glitch,
+            Setting::Filter => self.filter = defaults.filter,
+            Setting::Luma => self.luma = defaults.luma,
+            Setting::Algorithm => self.algorithm = defaults.algorithm,
+            Setting::Charset => self.charset = defaults.charset,
+            Setting::ColorMode => self.color_mode = defaults.color_mode,
+            Setting::Crt => self.crt = defaults.crt,
+            Setting::Retro => self.retro = defaults.retro,
+            Setting::Braille => self.braille = defaults.braille,
+            Setting::Edges => self.edges = defaults.edges,
+            Setting::ColorBoost => self.color_boost = defaults.color_boost,
+            Setting::Mirror => self.mirror = defaults.mirror,
+            Setting::Invert => self.invert = defaults.invert,
+            Setting::TargetFps => self.target_fps = defaults.target_fps,
+            Setting::Reset => self.reset_adjustments(),
+        }
+    }
+
+    /// Swaps what a terminal without Unicode could not draw for plain ASCII:
+    /// a ramp with other characters for the standard one, retro blocks and
+    /// braille for glyphs, and half blocks for glyphs in the same colors
+    pub fn fall_back_to_ascii(&mut self) {
+        if !self.charset.ramp.iter().all(char::is_ascii) {
+            self.charset = Charset::new(CHARSETS[0].0, CHARSETS[0].1);
+        }
+        self.retro = None;
+        self.braille = false;
+        if self.color_mode == ColorMode::HalfBlock {
+            self.color_mode = ColorMode::Truecolor;
+        }
+    }
+
+    /// Restores the image adjustments, leaving the charset, color mode and
+    /// frame rate alone
+    pub fn reset_adjustments(&mut self) {
+        let defaults = Settings::default();
+        self.brightness = defaults.brightness;
+        self.contrast = defaults.contrast;
+        self.gamma = defaults.gamma;
+        self.exposure = defaults.exposure;
+        self.clip = defaults.clip;
+        self.local_contrast = defaults.local_contrast;
+        self.tone_map = defaults.tone_map;
+        self.effect = defaults.effect;
+        self.glitch = defaults.glitch;
+        self.algorithm = defaults.algorithm;
+    }
+}
+
+pub fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Returns the choice `step` positions away from `current`, wrapping around
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, step: i32) -> T {
+    let index = choices.iter().position(|c| *c == current).unwrap_or(0) as i32;
+    choices[(index + step).rem_euclid(choices.len() as i32) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_the_builtin_ramps() {
+        for (name, ramp) in CHARSETS {
+            assert_eq!(Charset::parse(name, ramp), Ok(Charset::new(name, ramp)));
+        }
+    }
+
+    #[test]
+    fn parse_rejects_short_ramps() {
+        assert!(Charset::parse("custom", "").is_err());
+        assert!(Charset::parse("custom", "@").is_err());
+        assert!(Charset::parse("custom", " @").is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_ramps_longer_than_the_levels() {
+        let ramp: String = ('\u{100}'..).take(MAX_RAMP + 1).collect();
+        assert!(Charset::parse("custom", &ramp).is_err());
+        assert!(Charset::parse("custom", &ramp[..ramp.len() - 2]).is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_control_characters() {
+        assert!(Charset::parse("custom", " .\t@").is_err());
+        assert!(Charset::parse("custom", " .\n@").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_repeated_characters() {
+        assert!(Charset::parse("custom", " .:.@").is_err());
+    }
+
+    #[test]
+    fn sorted_orders_by_coverage() {
+        let charset = Charset::new("custom", "@ .");
+        assert_eq!(charset.sorted().unwrap().ramp, vec![' ', '.', '@']);
+        assert!(Charset::new("custom", " é@").sorted().is_none());
+    }
+
+    #[test]
+    fn reversed_ramps_are_noticed() {
+        assert!(Charset::new("custom", "@%#*+=-:. ").looks_reversed());
+        assert!(!Charset::new("custom", " .:-=+*#%@").looks_reversed());
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let charsets = Charset::builtin();
+        assert_eq!(charsets[0].cycle(&charsets, -1), charsets[2]);
+        assert_eq!(charsets[2].cycle(&charsets, 1), charsets[0]);
+        // a custom ramp starts over from the ends
+        let custom = Charset::new("custom", " @");
+        assert_eq!(custom.cycle(&charsets, 1), charsets[0]);
+        assert_eq!(custom.cycle(&charsets, -1), charsets[2]);
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::canvas::Canvas;
+
+/// Writes the glyphs of a frame to a new text file in `dir`
+pub fn save(canvas: &Canvas, dir: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    // frames of a burst can be saved within the same millisecond
+    let mut path = dir.join(format!("snapshot-{}.txt", millis));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("snapshot-{}-{}.txt", millis, n));
+        n += 1;
+    }
+
+    fs::write(&path, canvas.to_string())?;
+
+    Ok(path)
+}
+
+/// Saves a number of consecutive frames, spaced by an interval
+pub struct Burst {
+    remaining: u32,
+    interval: Duration,
+    next: Instant,
+    pub saved: u32,
+}
+
+impl Burst {
+    pub fn new(count: u32, interval: Duration) -> Burst {
+        Burst {
+            remaining: count,
+            interval,
+            next: Instant::now(),
+            saved: 0,
+        }
+    }
+
+    /// Returns whether the frame drawn now should be saved
+    pub fn due(&mut self) -> bool {
+        if self.remaining == 0 || Instant::now() < self.next {
+            return false;
+        }
+
+        self.remaining -= 1;
+        self.saved += 1;
+        self.next += self.interval;
+        true
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+}
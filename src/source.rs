@@ -0,0 +1,174 @@
+use image::{imageops, GrayImage, ImageBuffer, Rgb};
+use nokhwa::{pixel_format::RgbFormat, utils::FrameFormat, Camera};
+use std::path::{Path, PathBuf};
+
+use crate::ansi::{self, Animation};
+use crate::asciivid;
+use crate::bayer::BayerPattern;
+use crate::canvas::Canvas;
+use crate::raster::rasterize;
+use crate::yuv;
+
+/// A frame as the sources hand it over
+pub type RgbFrame = ImageBuffer<Rgb<u8>, Vec<u8>>;
+
+/// Where the viewer gets its frames from
+pub enum Source {
+    Camera {
+        camera: Camera,
+        /// Set for raw sensors, whose gray frames are really a Bayer mosaic
+        bayer: Option<BayerPattern>,
+    },
+    /// A still image, shown as the same frame over and over
+    Image { path: PathBuf, image: RgbFrame },
+    /// ANSI or ASCII art, or a compact recording, shown cell for cell rather
+    /// than dithered
+    Art { path: PathBuf, animation: Animation },
+}
+
+impl Source {
+    /// Opens an art file, a recording or an image, going by the extension
+    pub fn open_file(path: &Path) -> Result<Source, String> {
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(asciivid::EXTENSION))
+        {
+            return Ok(Source::Art {
+                path: path.to_path_buf(),
+                animation: Animation::timed(asciivid::load(path)?),
+            });
+        }
+        if ansi::is_art(path) {
+            return Ok(Source::Art {
+                path: path.to_path_buf(),
+                animation: Animation::new(ansi::load(path)?),
+            });
+        }
+        Source::open_image(path)
+    }
+
+    fn open_image(path: &Path) -> Result<Source, String> {
+        let image = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Ok(Source::Image {
+            path: path.to_path_buf(),
+            image: image.to_rgb8(),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Source::Camera { camera, .. } => camera.info().human_name(),
+            Source::Image { path, .. } | Source::Art { path, .. } => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// The mode a camera actually negotiated, which may differ from the one
+    /// requested, or the size of an image
+    pub fn mode(&self) -> String {
+        match self {
+            Source::Camera { camera, .. } => {
+                format!("{}@{}", camera.resolution(), camera.frame_rate())
+            }
+            Source::Image { image, .. } => format!("{}x{}", image.width(), image.height()),
+            Source::Art { animation, .. } => {
+                let art = animation.current();
+                match animation.len() {
+                    1 => format!("{}x{} cells", art.width, art.height),
+                    frames => format!("{}x{} cells, {} frames", art.width, art.height, frames),
+                }
+            }
+        }
+    }
+
+    pub fn frame(&mut self) -> Result<RgbFrame, String> {
+        match self {
+            Source::Camera { camera, bayer } => {
+                let frame = camera.frame().map_err(|e| e.to_string())?;
+                let (width, height) = (frame.resolution().width(), frame.resolution().height());
+                match (frame.source_frame_format(), bayer) {
+                    (FrameFormat::GRAY, Some(pattern)) => {
+                        pattern.demosaic(width, height, frame.buffer())
+                    }
+                    (FrameFormat::YUYV, _) => yuv::yuyv_to_rgb(width, height, frame.buffer()),
+                    (FrameFormat::NV12, _) => yuv::nv12_to_rgb(width, height, frame.buffer()),
+                    _ => frame.decode_image::<RgbFormat>().map_err(|e| e.to_string()),
+                }
+            }
+            Source::Image { image, .. } => Ok(image.clone()),
+            // what a terminal would show, for whatever looks at the pixels
+            Source::Art { animation, .. } => Ok(rasterize(animation.current())),
+        }
+    }
+
+    /// Grabs a frame as luma only, which skips the color conversion when the
+    /// camera sends luma as is
+    pub fn luma_frame(&mut self) -> Result<GrayImage, String> {
+        if let Source::Camera {
+            camera,
+            bayer: None,
+        } = self
+        {
+            let frame = camera.frame().map_err(|e| e.to_string())?;
+            let (width, height) = (frame.resolution().width(), frame.resolution().height());
+            if let Some(luma) =
+                yuv::luma(frame.source_frame_format(), width, height, frame.buffer())
+            {
+                return Ok(luma);
+            }
+            return frame
+                .decode_image::<RgbFormat>()
+                .map(|frame| imageops::grayscale(&frame))
+                .map_err(|e| e.to_string());
+        }
+
+        self.frame().map(|frame| imageops::grayscale(&frame))
+    }
+
+    /// The frames of an art source or a recording, which play at a speed of their own
+    pub fn animation(&self) -> Option<&Animation> {
+        match self {
+            Source::Art { animation, .. } => Some(animation),
+            _ => None,
+        }
+    }
+
+    pub fn animation_mut(&mut self) -> Option<&mut Animation> {
+        match self {
+            Source::Art { animation, .. } => Some(animation),
+            _ => None,
+        }
+    }
+
+    /// The frame of an art source to show as is, `None` for the other sources
+    pub fn art(&mut self, paused: bool) -> Option<Canvas> {
+        match self {
+            Source::Art { animation, .. } => Some(animation.frame(paused).clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Turns what a terminal pastes when a file is dropped on it into a path:
+/// surrounding quotes, a `file://` scheme and escaped spaces are removed
+pub fn clean_path(text: &str) -> PathBuf {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('\'')
+        .and_then(|t| t.strip_suffix('\''))
+        .or_else(|| text.strip_prefix('"').and_then(|t| t.strip_suffix('"')))
+        .unwrap_or(text);
+    let text = text.strip_prefix("file://").unwrap_or(text);
+
+    PathBuf::from(text.replace("\\ ", " "))
+}
+
+/// Whether pasted text is a URL rather than a path
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+    text.contains("://") && !text.starts_with("file://")
+}
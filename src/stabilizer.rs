@@ -0,0 +1,238 @@
+use image::{imageops, GrayImage, ImageBuffer};
+use std::f32::consts::PI;
+
+use crate::source::RgbFrame;
+
+/// Frames are compared at this size, a power of two for the FFT
+const SAMPLE_SIZE: usize = 64;
+
+/// How much of the measured camera path is followed each frame; the rest of
+/// the movement is jitter and gets compensated
+const SMOOTHING: f32 = 0.1;
+
+/// Compensation is capped to this fraction of the frame, beyond which the
+/// camera is more likely panning on purpose
+const MAX_SHIFT: f32 = 0.15;
+
+/// Cancels out the small shifts between frames of a shaky camera
+#[derive(Default)]
+pub struct Stabilizer {
+    /// Spectrum of the previous sample
+    previous: Option<Vec<Complex>>,
+    /// Where the camera went since the stabilizer started, in frame pixels
+    path: (f32, f32),
+    /// The same path without the jitter
+    smoothed: (f32, f32),
+}
+
+impl Stabilizer {
+    /// Shifts the frame back by how much it jittered since the previous ones
+    pub fn apply(&mut self, frame: RgbFrame) -> RgbFrame {
+        let (width, height) = frame.dimensions();
+        let spectrum = spectrum(&sample(&frame));
+
+        if let Some(previous) = self.previous.as_ref() {
+            let (dx, dy) = phase_correlation(previous, &spectrum);
+            self.path.0 += dx * width as f32 / SAMPLE_SIZE as f32;
+            self.path.1 += dy * height as f32 / SAMPLE_SIZE as f32;
+        }
+        self.previous = Some(spectrum);
+
+        self.smoothed.0 += (self.path.0 - self.smoothed.0) * SMOOTHING;
+        self.smoothed.1 += (self.path.1 - self.smoothed.1) * SMOOTHING;
+
+        let limit = (width as f32 * MAX_SHIFT, height as f32 * MAX_SHIFT);
+        let shift_x = (self.smoothed.0 - self.path.0).clamp(-limit.0, limit.0);
+        let shift_y = (self.smoothed.1 - self.path.1).clamp(-limit.1, limit.1);
+        // a deliberate pan drags the smoothed path along instead of drifting off
+        self.smoothed = (self.path.0 + shift_x, self.path.1 + shift_y);
+
+        translate(&frame, shift_x.round() as i64, shift_y.round() as i64)
+    }
+}
+
+/// Moves the image by the given offset, repeating the edge pixels into the uncovered area
+fn translate(frame: &RgbFrame, dx: i64, dy: i64) -> RgbFrame {
+    if dx == 0 && dy == 0 {
+        return frame.clone();
+    }
+
+    let (width, height) = frame.dimensions();
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let sx = (x as i64 - dx).clamp(0, width as i64 - 1) as u32;
+        let sy = (y as i64 - dy).clamp(0, height as i64 - 1) as u32;
+        *frame.get_pixel(sx, sy)
+    })
+}
+
+/// Downscales the frame to a windowed luma sample, the window keeping the
+/// image edges from looking like a shift
+fn sample(frame: &RgbFrame) -> GrayImage {
+    let size = SAMPLE_SIZE as u32;
+    let gray = imageops::grayscale(frame);
+    let mut sample = imageops::resize(&gray, size, size, imageops::FilterType::Triangle);
+
+    for (x, y, pixel) in sample.enumerate_pixels_mut() {
+        let wx = 0.5 - 0.5 * (2.0 * PI * x as f32 / (size - 1) as f32).cos();
+        let wy = 0.5 - 0.5 * (2.0 * PI * y as f32 / (size - 1) as f32).cos();
+        pixel[0] = (pixel[0] as f32 * wx * wy) as u8;
+    }
+
+    sample
+}
+
+/// Finds the translation from `previous` to `current`, in sample pixels
+fn phase_correlation(previous: &[Complex], current: &[Complex]) -> (f32, f32) {
+    // the normalized cross-power spectrum only keeps the phase difference,
+    // whose inverse transform peaks at the shift
+    let mut cross: Vec<Complex> = current
+        .iter()
+        .zip(previous)
+        .map(|(a, b)| {
+            let product = a.mul(b.conj());
+            let magnitude = product.norm();
+            if magnitude > f32::EPSILON {
+                product.scale(1.0 / magnitude)
+            } else {
+                Complex::default()
+            }
+        })
+        .collect();
+    fft_2d(&mut cross, true);
+
+    let peak = cross
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.re.total_cmp(&b.1.re))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    // shifts past the middle wrap around to negative ones
+    let unwrap = |v: usize| {
+        if v > SAMPLE_SIZE / 2 {
+            v as f32 - SAMPLE_SIZE as f32
+        } else {
+            v as f32
+        }
+    };
+
+    (unwrap(peak % SAMPLE_SIZE), unwrap(peak / SAMPLE_SIZE))
+}
+
+fn spectrum(sample: &GrayImage) -> Vec<Complex> {
+    let mut data: Vec<Complex> = sample
+        .pixels()
+        .map(|p| Complex {
+            re: p[0] as f32,
+            im: 0.0,
+        })
+        .collect();
+    fft_2d(&mut data, false);
+    data
+}
+
+#[derive(Clone, Copy, Default)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn add(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+
+    fn conj(self) -> Complex {
+        Complex {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Complex {
+        Complex {
+            re: self.re * factor,
+            im: self.im * factor,
+        }
+    }
+
+    fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+}
+
+/// Transforms a square `SAMPLE_SIZE` image in place, rows then columns
+fn fft_2d(data: &mut [Complex], inverse: bool) {
+    let mut column = vec![Complex::default(); SAMPLE_SIZE];
+
+    for row in data.chunks_mut(SAMPLE_SIZE) {
+        fft(row, inverse);
+    }
+    for x in 0..SAMPLE_SIZE {
+        for y in 0..SAMPLE_SIZE {
+            column[y] = data[y * SAMPLE_SIZE + x];
+        }
+        fft(&mut column, inverse);
+        for y in 0..SAMPLE_SIZE {
+            data[y * SAMPLE_SIZE + x] = column[y];
+        }
+    }
+}
+
+/// Iterative radix-2 Cooley-Tukey FFT; the inverse is left unscaled since
+/// only the position of its peak matters
+fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+
+    // bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        let step = Complex {
+            re: angle.cos(),
+            im: angle.sin(),
+        };
+        for start in (0..n).step_by(len) {
+            let mut twiddle = Complex { re: 1.0, im: 0.0 };
+            for k in 0..len / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + len / 2].mul(twiddle);
+                data[start + k] = even.add(odd);
+                data[start + k + len / 2] = even.sub(odd);
+                twiddle = twiddle.mul(step);
+            }
+        }
+        len <<= 1;
+    }
+}
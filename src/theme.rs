@@ -0,0 +1,139 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders},
+};
+
+use crate::config::Config;
+
+/// Colors of the interface around the image
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// List entries and other plain text
+    pub text: Style,
+    /// The selected entry of a list
+    pub highlight: Style,
+    pub border: Style,
+    pub title: Style,
+    pub status: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            text: Style::default().fg(Color::White),
+            highlight: Style::default().bg(Color::White).fg(Color::Black),
+            border: Style::default(),
+            title: Style::default(),
+            status: Style::default().bg(Color::White).fg(Color::Black),
+        }
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::default()),
+            // the terminal's own foreground stays readable on light backgrounds
+            "light" => Some(Theme {
+                text: Style::default().fg(Color::Reset),
+                highlight: Style::default().bg(Color::Blue).fg(Color::White),
+                border: Style::default().fg(Color::DarkGray),
+                title: Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+                status: Style::default().bg(Color::Blue).fg(Color::White),
+            }),
+            "contrast" => Some(Theme {
+                text: Style::default().fg(Color::White),
+                highlight: Style::default().bg(Color::Yellow).fg(Color::Black),
+                border: Style::default().fg(Color::Yellow),
+                title: Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                status: Style::default().bg(Color::Yellow).fg(Color::Black),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Starts from the theme named by `theme.name` and applies the custom colors on top
+    pub fn from_config(config: &Config) -> Result<Theme, String> {
+        let mut theme = match config.get("theme.name") {
+            Some(name) => Theme::builtin(name).ok_or_else(|| format!("Unknown theme: {}", name))?,
+            None => Theme::default(),
+        };
+
+        let color = |key: &str| config.get(key).map(parse_color).transpose();
+        if let Some(c) = color("theme.text")? {
+            theme.text = theme.text.fg(c);
+        }
+        if let Some(c) = color("theme.highlight_fg")? {
+            theme.highlight = theme.highlight.fg(c);
+        }
+        if let Some(c) = color("theme.highlight_bg")? {
+            theme.highlight = theme.highlight.bg(c);
+        }
+        if let Some(c) = color("theme.border")? {
+            theme.border = theme.border.fg(c);
+        }
+        if let Some(c) = color("theme.title")? {
+            theme.title = theme.title.fg(c);
+        }
+        if let Some(c) = color("theme.status_fg")? {
+            theme.status = theme.status.fg(c);
+        }
+        if let Some(c) = color("theme.status_bg")? {
+            theme.status = theme.status.bg(c);
+        }
+
+        Ok(theme)
+    }
+
+    /// A block with the given borders in the theme's colors
+    pub fn block(&self, borders: Borders) -> Block<'static> {
+        Block::default().borders(borders).border_style(self.border)
+    }
+
+    /// A bordered block with a title in the theme's colors
+    pub fn titled(&self, title: impl Into<String>) -> Block<'static> {
+        self.block(Borders::ALL)
+            .title(Span::styled(title.into(), self.title))
+    }
+}
+
+/// Reads a color name such as `light-blue`, or a hex color such as `#ffcc00`
+fn parse_color(value: &str) -> Result<Color, String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+            _ => Err(format!("Not a color: {}", value)),
+        };
+    }
+
+    let color = match value.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("Not a color: {}", value)),
+    };
+    Ok(color)
+}
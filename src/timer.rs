@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+/// Countdown lengths the self-timer cycles through, in seconds
+const DURATIONS: [u64; 3] = [3, 5, 10];
+
+/// Counts down before taking a snapshot
+pub struct SelfTimer {
+    pub seconds: u64,
+    started: Option<Instant>,
+}
+
+pub enum Countdown {
+    Idle,
+    /// Whole seconds left, rounded up
+    Running(u64),
+    /// The countdown just ended, which only happens once per start
+    Done,
+}
+
+impl Default for SelfTimer {
+    fn default() -> SelfTimer {
+        SelfTimer {
+            seconds: DURATIONS[0],
+            started: None,
+        }
+    }
+}
+
+impl SelfTimer {
+    pub fn cycle(&mut self) {
+        let index = DURATIONS
+            .iter()
+            .position(|d| *d == self.seconds)
+            .unwrap_or(0);
+        self.seconds = DURATIONS[(index + 1) % DURATIONS.len()];
+    }
+
+    /// Starts the countdown, or cancels the one running
+    pub fn toggle(&mut self) {
+        self.started = match self.started {
+            Some(_) => None,
+            None => Some(Instant::now()),
+        };
+    }
+
+    pub fn poll(&mut self) -> Countdown {
+        let started = match self.started {
+            Some(started) => started,
+            None => return Countdown::Idle,
+        };
+
+        let total = Duration::from_secs(self.seconds);
+        match total.checked_sub(started.elapsed()) {
+            Some(left) if !left.is_zero() => Countdown::Running(left.as_secs() + 1),
+            _ => {
+                self.started = None;
+                Countdown::Done
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+const MAX_TOASTS: usize = 4;
+
+/// A short message shown in a corner of the screen for a few seconds
+pub struct Toast {
+    pub message: String,
+    shown: Instant,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, message: impl Into<String>) {
+        let message = message.into();
+
+        // a repeated message only extends the one already on screen
+        if let Some(toast) = self.queue.iter_mut().find(|t| t.message == message) {
+            toast.shown = Instant::now();
+            return;
+        }
+
+        if self.queue.len() == MAX_TOASTS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Toast {
+            message,
+            shown: Instant::now(),
+        });
+    }
+
+    /// Drops the expired toasts and returns the remaining ones, oldest first
+    pub fn visible(&mut self) -> impl Iterator<Item = &Toast> {
+        self.queue.retain(|t| t.shown.elapsed() < TOAST_DURATION);
+        self.queue.iter()
+    }
+}
@@ -0,0 +1,1356 @@
+use chrono::Local;
+use image::{DynamicImage, ImageBuffer, Rgb};
+use nokhwa::utils::CameraIndex;
+use std::{cmp::Ordering, time::Duration};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Sparkline, Wrap},
+    Frame,
+};
+
+use crate::ansi::Animation;
+use crate::app::{App, Opening, RenderKey};
+use crate::audio::{Microphone, FLOOR_DB};
+use crate::backdrop::Backdrop;
+use crate::braille;
+use crate::canvas::{Canvas, Cell};
+use crate::clahe;
+use crate::color;
+use crate::dialog::Dialog;
+use crate::dither::{dither_image, luma, luma_histogram, Hysteresis};
+use crate::effects;
+use crate::exposure::AutoExposure;
+use crate::faces::FaceDetector;
+use crate::gallery::Gallery;
+use crate::halfblock;
+use crate::overlay::{
+    draw_big_number, draw_faces, draw_grid, draw_label, draw_logo, draw_markers, draw_outline,
+    draw_split, draw_ticker, Grid, Markers,
+};
+use crate::preview::Preview;
+use crate::privacy::Privacy;
+use crate::qr::QrScanner;
+use crate::recorder::{human_size, Recorder};
+use crate::resample;
+use crate::retro;
+use crate::settings::{on_off, ColorMode, Exposure, Setting, Settings};
+use crate::theme::Theme;
+use crate::timer::Countdown;
+use crate::viewport::Viewport;
+
+const MENU: &str = r#"
+Press '?' to show the controls.
+
+Known issues:
+ - The framerate decreases when the window size or camera resolution increase 
+ - The image is not very stable; lots of blinking and jittering
+"#;
+
+const CONTROLS: &str = r#"
+ - 'q' - quit the application
+ - 'up' and 'down' arrow to navigate the camera list
+ - 'enter' or a mouse click to select a camera
+ - '/' to filter the camera list by name
+ - 'page up' and 'page down' to page through a long camera list
+ - 'i' to show or hide the Info panel
+ - 'g' in the camera list to browse the saved snapshots
+ - 'a' in the camera list to calibrate the shape of the terminal cells, so that chunky glyphs keep the proportions of the image
+ - 'm' in the camera list to monitor every camera at once in a grid, 'm' or 'esc' to go back
+ - 'o' in the camera list to open an image, an ANSI art file (.ans, .asc, animated too) or an .asciivid recording, or paste its path or drop it on the terminal
+ - '1' to '9' in the camera list to reopen one of the files opened lately, listed under the preview
+ - 'spacebar' to pause the viewer
+ - '.' to step a single frame while paused, or to the next frame of an animation
+ - '-' and '+' to play an animation or a recording slower or faster, from 0.25x to 4x
+ - 'L' to loop part of an animation or a recording: once at its first frame, again at its last, a third time to play it all again
+ - 'left' and 'right' arrow to seek an animation or a recording 5 seconds back or ahead, whose time is shown in the view title and on a seek bar below
+ - 'A' to pause by itself when nothing moves, and resume on motion
+ - 'left' and 'right' arrow to rewind the last seconds while paused
+ - 'esc' to return to the main menu
+ - 'f' to toggle fullscreen
+ - 'H' to show or hide the luminance histogram
+ - 'g' to cycle the composition grid
+ - 'x' to cycle the crosshair and safe-area markers
+ - 'c' to show or hide the clock
+ - 'p' to scroll the text of a file across the bottom of the image, like a teleprompter
+ - 'D' to detect faces and draw boxes around them
+ - 'V' to replace the background behind you with ASCII art, learned from the empty scene
+ - 'Q' to scan for QR codes and show their text, which can be copied to the clipboard
+ - 'Z' to stabilize a shaky camera
+ - 'm' to show a level meter of the microphone next to the image
+ - 'W' to cycle the setting the microphone level drives, for audio-reactive visuals: off, brightness or contrast
+ - 'G' to cycle a retro console look, in the colors of the Game Boy, CGA or NES with chunky pixels
+ - '[' and ']' to make every glyph cover fewer or more pixels of the frame, for chunkier art drawn in the middle of the view; below one pixel the glyphs fill the view again
+ - 'I' to cycle the deinterlacing of an interlaced source, such as a capture card, between off, bob and linear; it is remembered per source
+ - 'U' to draw braille characters instead of the ramp, 2x4 dots to a cell for four times the detail
+ - 'E' to draw lines along the edges, such as the outline of a face, in the direction they run, and the ramp everywhere else
+ - 'K' to color the glyphs, cycling between mono, the 256 colors of the xterm palette and true color for terminals that support it
+ - 'O' to switch to ordered dithering, and between its 2x2, 4x4 and 8x8 Bayer matrices
+ - 'e' to edit the character ramp, where 'tab' sorts it by how much of a cell each character covers
+ - 'C' to cycle the character ramps, the built-in ones, those of the config and braille; the current one is shown in the view title
+ - 'r' to reset the brightness, contrast and the other image adjustments, the algorithm and the zoom
+ - 's' to save a snapshot of the frame as text
+ - 'S' to save a burst of consecutive snapshots
+ - 't' to start or cancel the self-timer, 'T' to switch it between 3, 5 and 10 seconds
+ - 'R' to start or stop recording the frames to a text file, to PNG images or to an .asciivid file
+ - '1' to '9' to switch to a saved preset, 'alt' + '1' to '9' to save the current look in one
+ - 'b' to flip between two looks, A and B, to compare them
+ - 'tab' to show or hide the settings panel
+ - 'B' to split the view, showing the image without the highlighted setting, or without any adjustment when the panel is hidden, on the left half
+ - 'up' and 'down' arrow to pick a setting, 'left' and 'right' to change it
+ - 'h', 'j', 'k' and 'l' work like the arrow keys, which pan the viewer when zoomed in
+ - scroll the mouse wheel to zoom and drag to pan the viewer
+ - drag with the right mouse button to hide a region of the frame, 'M' to remove them all
+ - '?' to show or hide this help, 'j' and 'k' or the arrows to scroll it
+"#;
+
+/// Below this size the layouts no longer fit, so nothing but a notice is drawn
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 10;
+
+pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        render_too_small(f, size);
+        return;
+    }
+
+    // the microphone may be driving the settings of this frame
+    let settings = app.render_settings();
+    let before = app.split.then(|| app.before_settings(&settings));
+
+    match app.source.as_mut() {
+        None if app.gallery.is_some() => {
+            if let Some(gallery) = app.gallery.as_mut() {
+                render_gallery(f, &app.theme, gallery, size);
+            }
+        }
+        None if app.calibration.is_some() => {
+            if let Some(aspect) = app.calibration {
+                render_calibration(f, &app.theme, aspect, app.unicode, size);
+            }
+        }
+        None if app.monitor.is_some() => {
+            if let Some(monitor) = app.monitor.as_mut() {
+                render_monitor(f, &app.theme, &app.cameras, monitor, &settings, size);
+            }
+        }
+        None => {
+            // the camera list takes the whole column when the Info panel is collapsed
+            let info_height = if app.show_info { 75 } else { 0 };
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(100 - info_height),
+                        Constraint::Percentage(info_height),
+                    ]
+                    .as_ref(),
+                )
+                .split(Rect::new(0, 0, size.width / 2, size.height));
+
+            let cameras: Vec<ListItem> = app
+                .menu
+                .items
+                .iter()
+                .map(|i| {
+                    let lines = vec![highlight_match(&app.cameras[*i].0, &app.filter)];
+                    ListItem::new(lines).style(app.theme.text)
+                })
+                .collect();
+
+            let mut title = if app.filtering {
+                format!("Cameras - /{}_", app.filter)
+            } else if !app.filter.is_empty() {
+                format!("Cameras - /{}", app.filter)
+            } else {
+                String::from("Cameras")
+            };
+
+            // keep track of the list scrolling so that mouse clicks land on the right camera
+            app.menu_area = Block::default().borders(Borders::ALL).inner(chunks[0]);
+            app.menu.scroll_into_view(app.menu_area.height.into());
+
+            let (page, pages) = app.menu.page(app.menu_area.height.into());
+            if pages > 1 {
+                title.push_str(&format!(" - page {}/{}", page, pages));
+            }
+
+            // create a List from all the cameras and highlight the currently selected one
+            let cameras = List::new(cameras)
+                .block(app.theme.titled(title))
+                .highlight_style(app.theme.highlight)
+                .highlight_symbol("> ");
+
+            f.render_stateful_widget(cameras, chunks[0], &mut app.menu.state);
+
+            let instructions = Paragraph::new(MENU)
+                .block(app.theme.titled("Info"))
+                .wrap(Wrap { trim: true });
+
+            if app.show_info {
+                f.render_widget(instructions, chunks[1]);
+            }
+
+            // show what the highlighted camera sees next to the list
+            let preview_area = Rect::new(
+                size.width / 2,
+                0,
+                size.width - size.width / 2,
+                size.height / 2,
+            );
+            let inner = Block::default().borders(Borders::ALL).inner(preview_area);
+            app.update_preview(inner.width, inner.height);
+
+            let text = match app.preview.as_ref() {
+                Some(preview) => preview.text.clone(),
+                None => String::new(),
+            };
+            let preview = Paragraph::new(text).block(app.theme.titled("Preview"));
+
+            f.render_widget(preview, preview_area);
+
+            // the files opened lately, under the preview, each with its digit
+            let recent_area = Rect::new(
+                preview_area.x,
+                preview_area.bottom(),
+                preview_area.width,
+                size.height - preview_area.height,
+            );
+            let recent: Vec<ListItem> = app
+                .recent
+                .iter()
+                .enumerate()
+                .map(|(i, path)| ListItem::new(format!("{} {}", i + 1, path)).style(app.theme.text))
+                .collect();
+            let recent = List::new(recent).block(app.theme.titled("Recent - 1-9 to open"));
+
+            f.render_widget(recent, recent_area);
+        }
+        Some(source) => {
+            // fullscreen gives every row to the image, status bar included,
+            // and animations get a seek bar on top of it
+            let seekable = source.animation().is_some_and(|a| a.len() > 1);
+            let status_height = match (app.fullscreen, seekable) {
+                (true, _) => 0,
+                (false, true) => 3,
+                (false, false) => 2,
+            };
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(status_height)].as_ref())
+                .split(size);
+
+            let (view_area, panel_area) = if app.show_settings {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(0), Constraint::Length(30)].as_ref())
+                    .split(chunks[0]);
+
+                (columns[0], Some(columns[1]))
+            } else {
+                (chunks[0], None)
+            };
+
+            let (view_area, meter_area) = if app.microphone.is_some() {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+                    .split(view_area);
+
+                (columns[0], Some(columns[1]))
+            } else {
+                (view_area, None)
+            };
+
+            let (view_area, histogram_area) = if app.show_histogram {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(8)].as_ref())
+                    .split(view_area);
+
+                (rows[0], Some(rows[1]))
+            } else {
+                (view_area, None)
+            };
+
+            let borders = if app.fullscreen {
+                Borders::NONE
+            } else {
+                Borders::ALL
+            };
+
+            // the image has to fit inside the borders of the view, in its
+            // middle and at its own aspect ratio
+            let view = Block::default().borders(borders).inner(view_area);
+            let view = match (app.cell_pixels, app.capture_size) {
+                (Some(pixels), Some(size)) => fixed_view(view, size, pixels, app.cell_aspect),
+                (None, Some(size)) => fitted_view(view, size, app.cell_aspect),
+                _ => view,
+            };
+            app.view_area = view;
+
+            let ramp = match settings.braille {
+                true => "braille",
+                false => settings.charset.name.as_str(),
+            };
+            let mut title = format!("View - {} {} - {}", source.name(), source.mode(), ramp);
+
+            // everything that affects how the paused frame is rendered
+            let key = RenderKey {
+                size: (view.width, view.height),
+                settings: settings.clone(),
+                viewport: app.viewport.clone(),
+                privacy: app.privacy.clone(),
+                before: before.clone(),
+            };
+
+            // while paused for lack of motion, keep watching the camera to resume on motion
+            if app.auto_paused {
+                if let Ok(frame) = source.luma_frame() {
+                    app.motion.update_luma(&frame);
+                }
+                if !app.auto_pause || app.motion.still_for() < app.auto_pause_after {
+                    app.auto_paused = false;
+                    app.paused = false;
+                    app.last_frame = None;
+                }
+            }
+
+            let mut canvas = if let Some(art) = source.art(app.paused) {
+                // art is shown cell for cell, without any of the image processing
+                if let Some(animation) = source.animation().filter(|a| a.len() > 1) {
+                    title.push_str(&format!(
+                        " - {}/{}",
+                        clock(animation.position()),
+                        clock(animation.duration())
+                    ));
+                    if animation.speed != 1.0 {
+                        title.push_str(&format!(" - {}x", animation.speed));
+                    }
+                    match (animation.loop_a, animation.loop_b) {
+                        (Some(a), Some(b)) => {
+                            title.push_str(&format!(" - loop {}-{}", a + 1, b + 1))
+                        }
+                        (Some(a), None) => title.push_str(&format!(" - loop from {}", a + 1)),
+                        _ => {}
+                    }
+                }
+                if app.paused {
+                    title.push_str(" - Paused");
+                }
+                art.crop(view.width.into(), view.height.into())
+            } else {
+                match app.last_frame.as_ref() {
+                    Some(img) => {
+                        match app.replay.offset() {
+                            Some(offset) => title
+                                .push_str(&format!(" - Paused (-{:.1}s)", offset.as_secs_f32())),
+                            None if app.auto_paused => title.push_str(" - Paused (no motion)"),
+                            None => title.push_str(" - Paused"),
+                        }
+
+                        // only render the paused frame again when something changed
+                        match app.render_cache.as_ref() {
+                            Some((cached, canvas)) if *cached == key => canvas.clone(),
+                            _ => {
+                                let mut canvas = render_frame(
+                                    img.clone(),
+                                    view,
+                                    &app.viewport,
+                                    &app.privacy,
+                                    &settings,
+                                    &mut app.exposure,
+                                    Some(&mut app.hysteresis),
+                                    app.show_histogram.then_some(&mut app.histogram),
+                                    app.faces.as_mut(),
+                                    app.qr.as_mut(),
+                                    app.backdrop.as_mut(),
+                                );
+                                if let Some(before) = before.as_ref() {
+                                    let original = render_frame(
+                                        img.clone(),
+                                        view,
+                                        &app.viewport,
+                                        &app.privacy,
+                                        before,
+                                        &mut app.exposure.clone(),
+                                        None,
+                                        None,
+                                        None,
+                                        None,
+                                        None,
+                                    );
+                                    canvas.paste_left_half(&original);
+                                }
+                                app.render_cache = Some((key, canvas.clone()));
+                                canvas
+                            }
+                        }
+                    }
+                    None => {
+                        app.render_cache = None;
+
+                        // get a new frame
+                        match source.frame() {
+                            Ok(decoded) => {
+                                app.capture_size = Some(decoded.dimensions());
+                                let decoded = app.deinterlace.apply(decoded);
+                                let decoded = match app.lens.as_mut() {
+                                    Some(lens) => lens.correct(&decoded),
+                                    None => decoded,
+                                };
+                                let decoded = match app.stabilizer.as_mut() {
+                                    Some(stabilizer) => stabilizer.apply(decoded),
+                                    None => decoded,
+                                };
+                                app.motion.update(&decoded);
+                                app.replay.push(&decoded);
+
+                                if app.auto_pause
+                                    && !app.paused
+                                    && app.motion.still_for() >= app.auto_pause_after
+                                {
+                                    app.paused = true;
+                                    app.auto_paused = true;
+                                }
+
+                                if app.paused {
+                                    app.last_frame = Some(decoded.clone());
+                                }
+
+                                // the same frame without the change, rendered before
+                                // auto-exposure moves on
+                                let original = before.as_ref().map(|before| {
+                                    render_frame(
+                                        decoded.clone(),
+                                        view,
+                                        &app.viewport,
+                                        &app.privacy,
+                                        before,
+                                        &mut app.exposure.clone(),
+                                        None,
+                                        None,
+                                        None,
+                                        None,
+                                        None,
+                                    )
+                                });
+
+                                let mut canvas = render_frame(
+                                    decoded,
+                                    view,
+                                    &app.viewport,
+                                    &app.privacy,
+                                    &settings,
+                                    &mut app.exposure,
+                                    Some(&mut app.hysteresis),
+                                    app.show_histogram.then_some(&mut app.histogram),
+                                    app.faces.as_mut(),
+                                    app.qr.as_mut(),
+                                    app.backdrop.as_mut(),
+                                );
+                                if let Some(original) = original {
+                                    canvas.paste_left_half(&original);
+                                }
+                                canvas
+                            }
+                            Err(e) => {
+                                app.toasts.push(format!("Could not read a frame: {}", e));
+                                Canvas::new(0, 0)
+                            }
+                        }
+                    }
+                }
+            };
+
+            app.fps.tick();
+            app.last_canvas = Some(canvas.clone());
+            app.poll_burst();
+
+            // a paused frame would only be written again and again
+            if let Some(recorder) = app.recorder.as_mut().filter(|_| !app.paused) {
+                if let Err(e) = recorder.write(&canvas) {
+                    app.recorder = None;
+                    app.toasts.push(format!("Recording stopped: {}", e));
+                }
+            }
+            if let Some(seconds) = app.backdrop.as_ref().and_then(Backdrop::learning_in) {
+                title.push_str(&format!(" - Learning the background in {}s", seconds));
+            }
+            if app.is_idle() {
+                title.push_str(" - Idle");
+            }
+            if let Some(recorder) = app.recorder.as_ref() {
+                title.push_str(&recording_indicator(recorder));
+            }
+
+            // overlays are drawn on top of the (possibly cached) render
+            if app.grid != Grid::Off {
+                draw_grid(&mut canvas, app.grid);
+            }
+            if app.markers != Markers::Off {
+                draw_markers(&mut canvas, app.markers);
+            }
+            if let Some(faces) = app.faces.as_ref() {
+                draw_faces(&mut canvas, &faces.found);
+            }
+            if let Some(code) = app.qr.as_ref().and_then(|qr| qr.found.as_ref()) {
+                draw_outline(&mut canvas, &code.corners);
+            }
+            if let Some(text) = app.qr.as_mut().and_then(|qr| qr.take_new()) {
+                app.dialog = Some(Dialog::qr_code(text));
+            }
+            if let Some((start, end)) = app.mask_drag {
+                let corner = |column: u16, row: u16| {
+                    (
+                        (column.saturating_sub(view.x) as f32 + 0.5) / view.width.max(1) as f32,
+                        (row.saturating_sub(view.y) as f32 + 0.5) / view.height.max(1) as f32,
+                    )
+                };
+                let (a, b) = (corner(start.0, start.1), corner(end.0, end.1));
+                draw_outline(&mut canvas, &[a, (b.0, a.1), b, (a.0, b.1)]);
+            }
+            if app.split {
+                draw_split(&mut canvas);
+            }
+            if let Some(prompter) = app.prompter.as_ref() {
+                let text = prompter.window(canvas.width);
+                draw_ticker(&mut canvas, &text);
+            }
+            if let Some(logo) = app.logo.as_ref() {
+                draw_logo(&mut canvas, logo);
+            }
+            if let Some(corner) = app.clock {
+                let time = Local::now().format("%H:%M:%S").to_string();
+                draw_label(&mut canvas, &time, corner);
+            }
+            match app.self_timer.poll() {
+                Countdown::Running(seconds) => draw_big_number(&mut canvas, seconds),
+                Countdown::Done => app.snapshot(),
+                Countdown::Idle => {}
+            }
+
+            let mut block = app.theme.block(borders);
+            if app.flashing() {
+                block = block.border_style(app.theme.highlight);
+            }
+            // the recording indicator stays visible even in fullscreen
+            if !app.fullscreen || app.recorder.is_some() {
+                block = block.title(Span::styled(title, app.theme.title));
+            }
+            let mut paragraph = Paragraph::new(canvas.to_text());
+            if app.is_idle() {
+                paragraph = paragraph.style(Style::default().add_modifier(Modifier::DIM));
+            }
+
+            f.render_widget(block, view_area);
+            f.render_widget(paragraph, view);
+
+            if let Some(area) = histogram_area {
+                render_histogram(f, &app.theme, &app.histogram, area);
+            }
+
+            if let (Some(area), Some(microphone)) = (meter_area, app.microphone.as_ref()) {
+                render_level_meter(f, &app.theme, microphone, area);
+            }
+
+            if let Some(area) = panel_area {
+                render_settings_panel(f, app, area);
+            }
+
+            if !app.fullscreen {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(1); 3].as_ref())
+                    .split(chunks[1]);
+                let animation = app.source.as_ref().and_then(|s| s.animation());
+                if let Some(animation) = animation.filter(|a| a.len() > 1) {
+                    f.render_widget(seek_bar(&app.theme, animation, rows[2].width), rows[2]);
+                }
+                f.render_widget(status_bar(app, view), rows[0]);
+                f.render_widget(settings_line(app), rows[1]);
+            }
+        }
+    }
+
+    if let Some(command) = app.command.as_ref() {
+        render_command_line(f, command, size);
+    }
+
+    if let Some(path) = app.open_prompt.as_ref() {
+        render_open_prompt(f, &app.theme, path, size);
+    }
+
+    if let Some(ramp) = app.charset_editor.as_ref() {
+        render_charset_editor(f, &app.theme, ramp, size);
+    }
+
+    if let Some(opening) = app.opening.as_ref() {
+        render_opening(f, app, opening, size);
+    }
+
+    if let Some(dialog) = app.dialog.as_ref() {
+        render_dialog(f, &app.theme, dialog, size);
+    }
+
+    render_toasts(f, app, size);
+
+    if app.show_help {
+        render_help(f, app, size);
+    }
+}
+
+/// Draws the controls as tall as they are, scrolling when the screen is not
+fn render_help<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    let width = (size.width * 4 / 5).max(MIN_WIDTH);
+    let inner_width = width.saturating_sub(2).max(1) as usize;
+    // the lines the controls take once wrapped to the overlay
+    let lines: u16 = CONTROLS
+        .lines()
+        .map(|line| line.chars().count().max(1).div_ceil(inner_width) as u16)
+        .sum();
+    let height = (lines + 2).min(size.height);
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    let max_scroll = lines.saturating_sub(height.saturating_sub(2));
+    app.help_scroll = app.help_scroll.min(max_scroll);
+    let title = match max_scroll {
+        0 => String::from("Controls"),
+        _ => format!(
+            "Controls - {}/{} - j/k to scroll",
+            app.help_scroll, max_scroll
+        ),
+    };
+
+    let help = Paragraph::new(CONTROLS)
+        .block(app.theme.titled(title))
+        .wrap(Wrap { trim: false })
+        .scroll((app.help_scroll, 0));
+
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+fn render_too_small<B: Backend>(f: &mut Frame<B>, size: Rect) {
+    let text = vec![
+        Spans::from("Terminal too small"),
+        Spans::from(format!(
+            "{}x{}, needs {}x{}",
+            size.width, size.height, MIN_WIDTH, MIN_HEIGHT
+        )),
+    ];
+    let top = size.height.saturating_sub(text.len() as u16) / 2;
+    let area = Rect::new(size.x, size.y + top, size.width, size.height - top);
+
+    f.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// Draws the `:` command being typed over the bottom line of the screen
+fn render_command_line<B: Backend>(f: &mut Frame<B>, command: &str, size: Rect) {
+    if size.height == 0 {
+        return;
+    }
+
+    let area = Rect::new(size.x, size.bottom() - 1, size.width, 1);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(format!(":{}_", command)), area);
+}
+
+fn render_open_prompt<B: Backend>(f: &mut Frame<B>, theme: &Theme, path: &str, size: Rect) {
+    let area = centered_rect(60, 30, size);
+    let text = vec![
+        Spans::from(format!("Path: {}_", path)),
+        Spans::from(""),
+        Spans::from("Type or paste the path of an image, or drop the file on the terminal."),
+        Spans::from("'enter' to open, 'esc' to cancel"),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(theme.titled("Open an image"))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn render_charset_editor<B: Backend>(f: &mut Frame<B>, theme: &Theme, ramp: &str, size: Rect) {
+    let area = centered_rect(60, 30, size);
+    let block = theme.titled("Charset editor");
+    let inner = block.inner(area);
+
+    // a dark to light gradient drawn with the ramp being edited
+    let chars: Vec<char> = ramp.chars().collect();
+    let width = inner.width as usize;
+    let gradient: String = if chars.len() >= 2 && width > 1 {
+        (0..width)
+            .map(|i| chars[(i * (chars.len() - 1) + (width - 1) / 2) / (width - 1)])
+            .collect()
+    } else {
+        String::new()
+    };
+
+    let hint = if chars.len() >= 2 {
+        Span::raw("'enter' to apply, 'tab' to sort from dark to light, 'esc' to cancel")
+    } else {
+        Span::styled(
+            "The ramp needs at least two characters",
+            Style::default().fg(Color::Red),
+        )
+    };
+
+    let text = vec![
+        Spans::from(format!("Ramp, from dark to light: {}_", ramp)),
+        Spans::from(""),
+        Spans::from(gradient),
+        Spans::from(""),
+        Spans::from(hint),
+    ];
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn render_gallery<B: Backend>(f: &mut Frame<B>, theme: &Theme, gallery: &mut Gallery, size: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .split(size);
+
+    let items: Vec<ListItem> = gallery
+        .list
+        .items
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            ListItem::new(name.into_owned()).style(theme.text)
+        })
+        .collect();
+
+    let title = match gallery.renaming.as_ref() {
+        Some(name) => format!("Rename to: {}_", name),
+        None => String::from("Snapshots - 'r' rename, 'd' delete, 'esc' back"),
+    };
+
+    let list = List::new(items)
+        .block(theme.titled(title))
+        .highlight_style(theme.highlight)
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, columns[0], &mut gallery.list.state);
+
+    let preview = Paragraph::new(gallery.preview.clone()).block(theme.titled("Preview"));
+
+    f.render_widget(preview, columns[1]);
+}
+
+/// Tiles every camera in a grid about as wide as it is tall, each with its name
+fn render_monitor<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    cameras: &[(String, CameraIndex)],
+    monitor: &mut [Preview],
+    settings: &Settings,
+    size: Rect,
+) {
+    if monitor.is_empty() {
+        let text = Paragraph::new("No camera found").block(theme.titled("Monitor"));
+        f.render_widget(text, size);
+        return;
+    }
+
+    let columns = (monitor.len() as f32).sqrt().ceil() as u16;
+    let rows = (monitor.len() as u16).div_ceil(columns);
+    let (width, height) = (size.width / columns, size.height / rows);
+
+    for (i, preview) in monitor.iter_mut().enumerate() {
+        let (column, row) = (i as u16 % columns, i as u16 / columns);
+        let area = Rect::new(
+            size.x + column * width,
+            size.y + row * height,
+            width,
+            height,
+        );
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        preview.refresh(inner.width, inner.height, settings);
+
+        let name = &cameras[preview.index].0;
+        let tile = Paragraph::new(preview.text.clone()).block(theme.titled(name.as_str()));
+        f.render_widget(tile, area);
+    }
+}
+
+/// Draws a square, with its diagonals, as tall as the screen allows for the
+/// cell aspect being tried, which looks square once the aspect is right
+fn render_calibration<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    aspect: f32,
+    unicode: bool,
+    size: Rect,
+) {
+    let block = theme.titled("Calibration");
+    let inner = block.inner(size);
+    let help = format!(
+        "Cells {:.2} times taller than wide - 'left' and 'right' until the square looks square, 'enter' to keep it, 'esc' to cancel",
+        aspect
+    );
+
+    // leave the bottom line to the help
+    let available = inner.height.saturating_sub(2) as f32;
+    let rows = available.min(inner.width as f32 / aspect).max(2.0);
+    let columns = (rows * aspect).round().max(2.0);
+    let (rows, columns) = (rows as usize, columns as usize);
+
+    let ink = if unicode { '█' } else { '#' };
+    let mut canvas = Canvas::new(columns, rows);
+    for y in 0..rows {
+        // the diagonals, where a row is as far down as a column is across
+        let x = (y as f32 + 0.5) / rows as f32 * columns as f32;
+        canvas.set(x as usize, y, Cell::new('\\'));
+        canvas.set(columns - 1 - x as usize, y, Cell::new('/'));
+        canvas.set(0, y, Cell::new(ink));
+        canvas.set(columns - 1, y, Cell::new(ink));
+    }
+    for x in 0..columns {
+        canvas.set(x, 0, Cell::new(ink));
+        canvas.set(x, rows - 1, Cell::new(ink));
+    }
+
+    let area = Rect::new(
+        inner.x + (inner.width.saturating_sub(columns as u16)) / 2,
+        inner.y + (inner.height.saturating_sub(rows as u16 + 2)) / 2,
+        (columns as u16).min(inner.width),
+        (rows as u16).min(inner.height),
+    );
+    let help_area = Rect::new(
+        inner.x,
+        inner.y + inner.height.saturating_sub(1),
+        inner.width,
+        1,
+    );
+
+    f.render_widget(block, size);
+    f.render_widget(Paragraph::new(canvas.to_text()), area);
+    f.render_widget(Paragraph::new(help).style(theme.text), help_area);
+}
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+fn render_opening<B: Backend>(f: &mut Frame<B>, app: &App, opening: &Opening, size: Rect) {
+    let frame = (opening.started.elapsed().as_millis() / 80) as usize % SPINNER.len();
+    let text = format!(
+        "{} Opening {}... ('esc' to cancel)",
+        SPINNER[frame], app.cameras[opening.index].0
+    );
+
+    let width = (text.chars().count() as u16 + 4).min(size.width);
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + size.height.saturating_sub(3) / 2,
+        width,
+        3.min(size.height),
+    );
+    let paragraph = Paragraph::new(text).block(app.theme.block(Borders::ALL));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn render_dialog<B: Backend>(f: &mut Frame<B>, theme: &Theme, dialog: &Dialog, size: Rect) {
+    let area = centered_rect(50, 30, size);
+
+    let mut options = Vec::new();
+    for (i, option) in dialog.options.iter().enumerate() {
+        let style = if i == dialog.selected {
+            theme.highlight
+        } else {
+            Style::default()
+        };
+        options.push(Span::styled(format!(" {} ", option), style));
+        options.push(Span::raw("  "));
+    }
+
+    let text = vec![
+        Spans::from(dialog.message.clone()),
+        Spans::from(""),
+        Spans::from(options),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(dialog.title)
+                .border_style(Style::default().fg(Color::Red)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Stacks the pending toasts in the bottom right corner, newest at the bottom
+fn render_toasts<B: Backend>(f: &mut Frame<B>, app: &mut App, size: Rect) {
+    // stay clear of the status lines
+    let mut bottom = size.height.saturating_sub(2);
+
+    for toast in app.toasts.visible().collect::<Vec<_>>().into_iter().rev() {
+        let width = (toast.message.chars().count() as u16 + 4).min(size.width);
+        if bottom < 3 {
+            break;
+        }
+
+        let area = Rect::new(size.width - width, bottom - 3, width, 3);
+        let paragraph = Paragraph::new(toast.message.clone())
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::Yellow));
+
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+        bottom -= 3;
+    }
+}
+
+/// Splits a camera name into spans with the part matching the filter emphasized
+fn highlight_match(name: &str, filter: &str) -> Spans<'static> {
+    // lowercasing may change the length of a character, so every byte of the
+    // lowercased name remembers the character of the name it comes from
+    let mut lowercase = String::new();
+    let mut origins = Vec::new();
+    for (i, ch) in name.char_indices() {
+        for lower in ch.to_lowercase() {
+            lowercase.push(lower);
+        }
+        origins.resize(lowercase.len(), (i, i + ch.len_utf8()));
+    }
+
+    let filter = filter.to_lowercase();
+    let found = match lowercase.find(&filter) {
+        Some(found) if !filter.is_empty() => found,
+        _ => return Spans::from(name.to_string()),
+    };
+    let start = origins[found].0;
+    let end = origins[found + filter.len() - 1].1;
+
+    Spans::from(vec![
+        Span::raw(name[..start].to_string()),
+        Span::styled(
+            name[start..end].to_string(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(name[end..].to_string()),
+    ])
+}
+
+/// The part of the view in its middle that a frame of the given size takes
+/// when every glyph covers `pixels` pixels across, and as many more down as
+/// cells are taller than wide
+fn fixed_view(view: Rect, size: (u32, u32), pixels: u32, cell_aspect: f32) -> Rect {
+    // the panels can leave no room at all, with nothing to center in it
+    if view.width == 0 || view.height == 0 {
+        return view;
+    }
+    let width = (size.0 / pixels).clamp(1, view.width.into()) as u16;
+    let height = (size.1 as f32 / (pixels as f32 * cell_aspect)) as u32;
+    let height = height.clamp(1, view.height.into()) as u16;
+    centered(view, width, height)
+}
+
+/// The largest part of the view in its middle that shows a frame of the given
+/// size without stretching it, leaving bars on the sides or above and below
+fn fitted_view(view: Rect, size: (u32, u32), cell_aspect: f32) -> Rect {
+    if view.width == 0 || view.height == 0 || size.0 == 0 || size.1 == 0 {
+        return view;
+    }
+    // how many columns a row of glyphs takes for the frame to look right
+    let columns_per_row = size.0 as f32 * cell_aspect / size.1 as f32;
+    let width = (view.height as f32 * columns_per_row).round() as u32;
+    let (width, height) = if width <= view.width.into() {
+        (width, view.height.into())
+    } else {
+        let height = (view.width as f32 / columns_per_row).round() as u32;
+        (view.width.into(), height)
+    };
+    let width = width.clamp(1, view.width.into()) as u16;
+    let height = height.clamp(1, view.height.into()) as u16;
+    centered(view, width, height)
+}
+
+fn centered(view: Rect, width: u16, height: u16) -> Rect {
+    Rect::new(
+        view.x + (view.width - width) / 2,
+        view.y + (view.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Turns a frame into text, filling in the luma histogram of what is shown and
+/// looking for faces and QR codes in it and putting the art behind the subject if asked to
+#[allow(clippy::too_many_arguments)]
+fn render_frame(
+    frame: ImageBuffer<Rgb<u8>, Vec<u8>>,
+    view: Rect,
+    viewport: &Viewport,
+    privacy: &Privacy,
+    settings: &Settings,
+    exposure: &mut AutoExposure,
+    hysteresis: Option<&mut Hysteresis>,
+    histogram: Option<&mut [u64; 256]>,
+    faces: Option<&mut FaceDetector>,
+    qr: Option<&mut QrScanner>,
+    backdrop: Option<&mut Backdrop>,
+) -> Canvas {
+    // hide the private regions before anything else sees the frame
+    let mut image = DynamicImage::from(frame);
+    privacy.apply(&mut image);
+
+    // rezise the visible part of the image
+    let mut image = viewport.apply(image);
+    if let Some(qr) = qr {
+        qr.scan(&image, settings.mirror);
+    }
+    if settings.mirror {
+        image = image.fliph();
+    }
+    if let Some(faces) = faces {
+        faces.detect(&image);
+    }
+    if settings.local_contrast {
+        image = clahe::equalize(&image);
+    }
+    let mut image = settings.effect.apply(image);
+    if settings.glitch > 0.0 {
+        image = effects::glitch(&image, settings.glitch);
+    }
+
+    // the modes that draw something other than the ramp do their own resizing
+    let drawn = if let Some(palette) = settings.retro {
+        Some(retro::render(&image, view, palette, settings))
+    } else if settings.braille {
+        Some(braille::render(&image, view, settings))
+    } else if settings.color_mode == ColorMode::HalfBlock {
+        Some(halfblock::render(&image, view, settings))
+    } else {
+        None
+    };
+    if let Some(canvas) = drawn {
+        if let Some(histogram) = histogram {
+            *histogram = luma_histogram(&image);
+        }
+        return canvas;
+    }
+    let colors = resample::resize(
+        &image,
+        view.width.into(),
+        view.height.into(),
+        settings.filter,
+    );
+    // everything from here on only looks at the luma, until the glyphs are colored
+    let image = DynamicImage::ImageLuma8(luma(&colors, settings.luma));
+
+    let image = match settings.exposure {
+        Exposure::Auto => exposure.apply(&image),
+        Exposure::Normalize => image,
+    };
+
+    if let Some(histogram) = histogram {
+        *histogram = luma_histogram(&image);
+    }
+
+    let mut canvas = match backdrop {
+        Some(backdrop) => {
+            let mut canvas = dither_image(image.clone(), settings, hysteresis);
+            backdrop.composite(&image, &mut canvas);
+            canvas
+        }
+        None => dither_image(image, settings, hysteresis),
+    };
+    color::colorize(&mut canvas, &colors, settings);
+    canvas
+}
+
+fn render_histogram<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    histogram: &[u64; 256],
+    area: Rect,
+) {
+    let block = theme.block(Borders::ALL);
+    let inner = block.inner(area);
+
+    // the lowest and highest levels of the frame, before any clipping
+    let min = histogram.iter().position(|c| *c > 0).unwrap_or(0);
+    let max = histogram.iter().rposition(|c| *c > 0).unwrap_or(255);
+    let title = format!("Histogram - min {} max {}", min, max);
+
+    // one bar per column, each covering an equal share of the 256 levels
+    let bars = inner.width.max(1) as usize;
+    let data: Vec<u64> = (0..bars)
+        .map(|bar| {
+            let start = bar * 256 / bars;
+            let end = ((bar + 1) * 256 / bars).max(start + 1).min(256);
+            histogram[start..end].iter().sum()
+        })
+        .collect();
+
+    let sparkline = Sparkline::default()
+        .block(block.title(Span::styled(title, theme.title)))
+        .data(&data)
+        .style(theme.text);
+
+    f.render_widget(sparkline, area);
+}
+
+/// Draws the microphone level as a column that fills up from the bottom,
+/// turning yellow then red near full scale
+fn render_level_meter<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    microphone: &Microphone,
+    area: Rect,
+) {
+    let decibels = microphone.decibels();
+    let block = theme
+        .block(Borders::ALL)
+        .title(Span::styled(format!("{:.0}", decibels), theme.title));
+    let inner = block.inner(area);
+
+    let height = inner.height as usize;
+    let filled = ((1.0 - decibels / FLOOR_DB) * height as f32).round() as usize;
+    let lines: Vec<Spans> = (0..height)
+        .map(|row| {
+            // rows are counted from the bottom, where the meter starts
+            let level = height - row;
+            if level > filled {
+                return Spans::from("");
+            }
+            let color = match level * 10 / height.max(1) {
+                9.. => Color::Red,
+                7 | 8 => Color::Yellow,
+                _ => Color::Green,
+            };
+            Spans::from(Span::styled(
+                "█".repeat(inner.width as usize),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_settings_panel<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .settings_panel
+        .items
+        .iter()
+        .map(|setting| {
+            let line = match setting {
+                Setting::Reset => setting.label().to_string(),
+                _ => format!(
+                    "{:<12}< {} >",
+                    setting.label(),
+                    app.settings.value(*setting)
+                ),
+            };
+            ListItem::new(Spans::from(line)).style(app.theme.text)
+        })
+        .collect();
+
+    let panel = List::new(items)
+        .block(app.theme.titled("Settings"))
+        .highlight_style(app.theme.highlight);
+
+    f.render_stateful_widget(panel, area, &mut app.settings_panel.state);
+}
+
+/// Returns a rectangle of the given percentage size centered inside `r`
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// A blinking dot, the time spent recording and the size of the file so far
+fn recording_indicator(recorder: &Recorder) -> String {
+    let elapsed = recorder.elapsed();
+    let dot = if elapsed.subsec_millis() < 500 {
+        '●'
+    } else {
+        ' '
+    };
+    let secs = elapsed.as_secs();
+
+    format!(
+        " - {} REC {:02}:{:02} ({})",
+        dot,
+        secs / 60,
+        secs % 60,
+        human_size(recorder.bytes)
+    )
+}
+
+/// Formats a time as minutes and seconds, e.g. `1:05`
+fn clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// How far an animation has played, with the ends of its loop marked by `[`
+/// and `]`
+fn seek_bar(theme: &Theme, animation: &Animation, width: u16) -> Paragraph<'static> {
+    let width = usize::from(width.saturating_sub(2)).max(1);
+    let last = animation.len().saturating_sub(1).max(1);
+    let column = |frame: usize| frame * (width - 1) / last;
+
+    let head = column(animation.current_index());
+    let mut bar: Vec<char> = (0..width)
+        .map(|x| match x.cmp(&head) {
+            Ordering::Less => '━',
+            Ordering::Equal => '●',
+            Ordering::Greater => '─',
+        })
+        .collect();
+    if let Some(a) = animation.loop_a {
+        bar[column(a)] = '[';
+    }
+    if let Some(b) = animation.loop_b {
+        bar[column(b)] = ']';
+    }
+
+    let text = format!(" {}", bar.into_iter().collect::<String>());
+    Paragraph::new(text).style(theme.status)
+}
+
+fn status_bar(app: &App, view: Rect) -> Paragraph<'static> {
+    let capture = match app.capture_size {
+        Some((width, height)) => format!("{}x{}", width, height),
+        None => String::from("-"),
+    };
+
+    let mode = match (app.vim_keys, app.show_settings) {
+        (true, true) => "-- ADJUST -- | ",
+        (true, false) => "-- NORMAL -- | ",
+        (false, _) => "",
+    };
+
+    let text = format!(
+        " {}{:.1}/{} fps | capture {} | render {}x{}",
+        mode, app.fps.fps, app.settings.target_fps, capture, view.width, view.height,
+    );
+
+    Paragraph::new(text).style(app.theme.status)
+}
+
+/// Sums up every toggle that changes how the image looks
+fn settings_line(app: &App) -> Paragraph<'static> {
+    let settings = &app.settings;
+    let side = match (&app.alternate, app.showing_b) {
+        (None, _) => "",
+        (Some(_), false) => "[A] ",
+        (Some(_), true) => "[B] ",
+    };
+    let text = format!(
+        " {}{} | charset {} | {} | zoom {:.1}x | mirror {} | invert {}",
+        side,
+        settings.algorithm.name(),
+        settings.charset.name,
+        settings.color_mode.name(),
+        app.viewport.zoom,
+        on_off(settings.mirror),
+        on_off(settings.invert),
+    );
+
+    Paragraph::new(text).style(app.theme.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The part of the name that is highlighted
+    fn highlighted(name: &str, filter: &str) -> Option<String> {
+        let spans = highlight_match(name, filter);
+        spans.0.get(1).map(|span| span.content.to_string())
+    }
+
+    #[test]
+    fn highlights_the_match_regardless_of_case() {
+        assert_eq!(
+            highlighted("Front Camera", "CAM"),
+            Some(String::from("Cam"))
+        );
+        assert_eq!(highlighted("Front Camera", "rear"), None);
+        assert_eq!(highlighted("Front Camera", ""), None);
+    }
+
+    #[test]
+    fn characters_that_lowercase_longer_are_mapped_back() {
+        // 'İ' lowercases to 'i' and a combining dot, a byte longer
+        assert_eq!(
+            highlighted("İstanbul", "i\u{307}s"),
+            Some(String::from("İs"))
+        );
+        assert_eq!(
+            highlighted("i\u{307}x", "İ"),
+            Some(String::from("i\u{307}"))
+        );
+        assert_eq!(highlighted("Kamera İ", "a i"), Some(String::from("a İ")));
+    }
+
+    #[test]
+    fn wide_frames_get_bars_above_and_below() {
+        // 640x480 with cells twice as tall as wide takes 8 columns every 3 rows
+        let view = fitted_view(Rect::new(1, 1, 80, 60), (640, 480), 2.0);
+        assert_eq!(view, Rect::new(1, 16, 80, 30));
+    }
+
+    #[test]
+    fn tall_frames_get_bars_on_the_sides() {
+        let view = fitted_view(Rect::new(0, 0, 80, 20), (640, 480), 2.0);
+        assert_eq!(view, Rect::new(13, 0, 53, 20));
+    }
+
+    #[test]
+    fn fitted_views_never_vanish() {
+        let view = fitted_view(Rect::new(0, 0, 1, 40), (1920, 10), 2.0);
+        assert_eq!(view, Rect::new(0, 19, 1, 1));
+        let empty = Rect::new(0, 0, 0, 10);
+        assert_eq!(fitted_view(empty, (640, 480), 2.0), empty);
+    }
+}
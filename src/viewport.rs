@@ -0,0 +1,65 @@
+use image::{DynamicImage, GenericImageView};
+
+const MAX_ZOOM: f32 = 8.0;
+
+/// The part of the camera frame that is shown in the viewer
+#[derive(Clone, PartialEq)]
+pub struct Viewport {
+    pub zoom: f32,
+    /// Center of the visible region, relative to the frame size
+    center: (f32, f32),
+}
+
+impl Default for Viewport {
+    fn default() -> Viewport {
+        Viewport {
+            zoom: 1.0,
+            center: (0.5, 0.5),
+        }
+    }
+}
+
+impl Viewport {
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        self.clamp_center();
+    }
+
+    /// Moves the image by a fraction of the visible region, so that dragging
+    /// the mouse across the whole view moves the image by one view width
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.center.0 -= dx / self.zoom;
+        self.center.1 -= dy / self.zoom;
+        self.clamp_center();
+    }
+
+    // keep the visible region inside the frame
+    fn clamp_center(&mut self) {
+        let half = 0.5 / self.zoom;
+        self.center.0 = self.center.0.clamp(half, 1.0 - half);
+        self.center.1 = self.center.1.clamp(half, 1.0 - half);
+    }
+
+    /// Maps a point of the visible region to the frame, both as fractions of their size
+    pub fn to_frame(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.center.0 + (x - 0.5) / self.zoom,
+            self.center.1 + (y - 0.5) / self.zoom,
+        )
+    }
+
+    /// Crops the frame down to the visible region
+    pub fn apply(&self, image: DynamicImage) -> DynamicImage {
+        if self.zoom <= 1.0 {
+            return image;
+        }
+
+        let (width, height) = image.dimensions();
+        let crop_width = ((width as f32 / self.zoom) as u32).max(1);
+        let crop_height = ((height as f32 / self.zoom) as u32).max(1);
+        let x = ((self.center.0 * width as f32) as u32).saturating_sub(crop_width / 2);
+        let y = ((self.center.1 * height as f32) as u32).saturating_sub(crop_height / 2);
+
+        image.crop_imm(x, y, crop_width, crop_height)
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts JSON events to a URL from a background thread, so that a slow
+/// endpoint never holds up the viewer. Only plain HTTP is spoken, which is
+/// what automation servers on the local network usually listen to.
+pub struct Webhook {
+    sender: Sender<String>,
+    /// Why events could not be posted, to be shown to the user
+    pub errors: Receiver<String>,
+}
+
+impl Webhook {
+    /// Checks the URL and starts the thread that posts to it
+    pub fn start(url: &str) -> Result<Webhook, String> {
+        let Endpoint {
+            address,
+            host,
+            path,
+        } = Endpoint::parse(url)?;
+
+        let (sender, events) = mpsc::channel::<String>();
+        let (error_sender, errors) = mpsc::channel();
+        thread::spawn(move || {
+            for body in events {
+                if let Err(e) = post(&address, &host, &path, &body) {
+                    let _ = error_sender.send(format!("{}: {}", host, e));
+                }
+            }
+        });
+
+        Ok(Webhook { sender, errors })
+    }
+
+    /// Queues a JSON body to be posted
+    pub fn post(&self, body: String) {
+        // the thread only stops with the webhook
+        let _ = self.sender.send(body);
+    }
+}
+
+/// Where a webhook posts to
+#[derive(PartialEq, Debug)]
+struct Endpoint {
+    /// `host:port` to connect to, the port being 80 unless the URL gives one
+    address: String,
+    /// The host as the URL writes it, for the `Host` header
+    host: String,
+    path: String,
+}
+
+impl Endpoint {
+    /// Splits an `http://host[:port][/path]` URL, where an IPv6 host is
+    /// written in brackets, e.g. `http://[::1]:8123/hook`
+    fn parse(url: &str) -> Result<Endpoint, String> {
+        let rest = match url.strip_prefix("http://") {
+            Some(rest) => rest,
+            None if url.starts_with("https://") => {
+                return Err(format!("Only http:// webhooks are supported: {}", url))
+            }
+            None => return Err(format!("Not a webhook URL: {}", url)),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/"),
+        };
+
+        // the colons of an IPv6 address are not the one before the port
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(inner) => match inner.split_once(']') {
+                Some((ip, port)) if !ip.is_empty() => (&authority[..ip.len() + 2], port),
+                _ => return Err(format!("Not a webhook URL: {}", url)),
+            },
+            None => match authority.find(':') {
+                Some(colon) => authority.split_at(colon),
+                None => (authority, ""),
+            },
+        };
+        let port = match port.strip_prefix(':') {
+            Some(port) => port
+                .parse::<u16>()
+                .map_err(|_| format!("Not a webhook port: {}", port))?,
+            None if port.is_empty() => 80,
+            None => return Err(format!("Not a webhook URL: {}", url)),
+        };
+        if host.is_empty() {
+            return Err(format!("Not a webhook URL: {}", url));
+        }
+
+        Ok(Endpoint {
+            address: format!("{}:{}", host, port),
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+fn post(address: &str, host: &str, path: &str, body: &str) -> io::Result<()> {
+    let address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown host"))?;
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+
+    // only the status line matters, e.g. `HTTP/1.1 200 OK`
+    let mut response = [0; 64];
+    let read = stream.read(&mut response)?;
+    let status = String::from_utf8_lossy(&response[..read]);
+    let status = status.lines().next().unwrap_or("");
+    match status.split(' ').nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(io::Error::other(format!("the server answered {}", status))),
+    }
+}
+
+/// Quotes text as a JSON string
+pub fn json_string(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(address: &str, host: &str, path: &str) -> Result<Endpoint, String> {
+        Ok(Endpoint {
+            address: address.to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    #[test]
+    fn splits_the_url() {
+        assert_eq!(
+            Endpoint::parse("http://home.local/api/hook"),
+            endpoint("home.local:80", "home.local", "/api/hook")
+        );
+        assert_eq!(
+            Endpoint::parse("http://10.0.0.2:8123/hook?a=b"),
+            endpoint("10.0.0.2:8123", "10.0.0.2", "/hook?a=b")
+        );
+        assert_eq!(
+            Endpoint::parse("http://home.local"),
+            endpoint("home.local:80", "home.local", "/")
+        );
+    }
+
+    #[test]
+    fn ipv6_hosts_keep_their_colons() {
+        assert_eq!(
+            Endpoint::parse("http://[::1]/hook"),
+            endpoint("[::1]:80", "[::1]", "/hook")
+        );
+        assert_eq!(
+            Endpoint::parse("http://[fe80::1]:8080"),
+            endpoint("[fe80::1]:8080", "[fe80::1]", "/")
+        );
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_post_to() {
+        for url in [
+            "https://home.local/hook",
+            "ftp://home.local",
+            "http:///hook",
+            "http://:80/hook",
+            "http://home.local:port/hook",
+            "http://home.local:99999",
+            "http://[::1/hook",
+            "http://[]/hook",
+            "http://[::1]x/hook",
+        ] {
+            assert!(Endpoint::parse(url).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(json_string("C:\\dir"), "\"C:\\\\dir\"");
+        assert_eq!(json_string("a\nb\r\tc"), "\"a\\nb\\r\\tc\"");
+        assert_eq!(json_string("\u{1}\u{1f}"), "\"\\u0001\\u001f\"");
+        assert_eq!(json_string("é ░"), "\"é ░\"");
+    }
+}
@@ -0,0 +1,120 @@
+use image::{GrayImage, ImageBuffer, Rgb};
+use nokhwa::utils::FrameFormat;
+
+use crate::source::RgbFrame;
+
+/// Converts a YUYV 4:2:2 frame, where every pair of pixels shares its chroma
+pub fn yuyv_to_rgb(width: u32, height: u32, data: &[u8]) -> Result<RgbFrame, String> {
+    check_size("YUYV", data, (width * height * 2) as usize)?;
+
+    Ok(ImageBuffer::from_fn(width, height, |x, y| {
+        let pair = ((y * width + x) / 2 * 4) as usize;
+        let luma = data[pair + (x as usize % 2) * 2];
+        ycbcr_to_rgb(luma, data[pair + 1], data[pair + 3])
+    }))
+}
+
+/// Converts an NV12 frame, a full luma plane followed by a plane of
+/// interleaved chroma at half the resolution in both directions
+pub fn nv12_to_rgb(width: u32, height: u32, data: &[u8]) -> Result<RgbFrame, String> {
+    // rows of the chroma plane hold a byte pair for every two pixels, rounded up
+    let luma_size = (width * height) as usize;
+    let chroma_stride = width.div_ceil(2) as usize * 2;
+    check_size(
+        "NV12",
+        data,
+        luma_size + chroma_stride * height.div_ceil(2) as usize,
+    )?;
+
+    Ok(ImageBuffer::from_fn(width, height, |x, y| {
+        let luma = data[(y * width + x) as usize];
+        let chroma = luma_size + (y / 2) as usize * chroma_stride + (x / 2) as usize * 2;
+        ycbcr_to_rgb(luma, data[chroma], data[chroma + 1])
+    }))
+}
+
+/// Takes the luma straight out of the formats that store it as is, which
+/// saves converting to color for consumers that only look at brightness
+pub fn luma(format: FrameFormat, width: u32, height: u32, data: &[u8]) -> Option<GrayImage> {
+    let size = (width * height) as usize;
+    match format {
+        FrameFormat::GRAY | FrameFormat::NV12 if data.len() >= size => {
+            GrayImage::from_raw(width, height, data[..size].to_vec())
+        }
+        FrameFormat::YUYV if data.len() >= size * 2 => {
+            GrayImage::from_raw(width, height, data.iter().step_by(2).copied().collect())
+        }
+        _ => None,
+    }
+}
+
+fn check_size(format: &str, data: &[u8], expected: usize) -> Result<(), String> {
+    if data.len() < expected {
+        return Err(format!(
+            "a {} frame needs {} bytes, the camera sent {}",
+            format,
+            expected,
+            data.len()
+        ));
+    }
+    Ok(())
+}
+
+/// BT.601 limited range, what webcams send
+fn ycbcr_to_rgb(y: u8, u: u8, v: u8) -> Rgb<u8> {
+    let y = (y as f32 - 16.0) * 1.164;
+    let u = u as f32 - 128.0;
+    let v = v as f32 - 128.0;
+
+    Rgb([
+        (y + 1.596 * v).clamp(0.0, 255.0) as u8,
+        (y - 0.392 * u - 0.813 * v).clamp(0.0, 255.0) as u8,
+        (y + 2.017 * u).clamp(0.0, 255.0) as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: u8 = 16;
+    const WHITE: u8 = 235;
+
+    #[test]
+    fn yuyv_pairs_share_their_chroma() {
+        let frame = yuyv_to_rgb(2, 1, &[BLACK, 128, WHITE, 128]).unwrap();
+        assert_eq!(frame.get_pixel(0, 0), &Rgb([0, 0, 0]));
+        assert_eq!(frame.get_pixel(1, 0), &Rgb([254, 254, 254]));
+
+        // red has the most of Cr, blue of Cb
+        let frame = yuyv_to_rgb(2, 1, &[128, 90, 128, 240]).unwrap();
+        let Rgb([red, green, blue]) = *frame.get_pixel(0, 0);
+        assert!(red > green && red > blue);
+    }
+
+    #[test]
+    fn nv12_chroma_covers_two_by_two_pixels() {
+        // 3x3 luma, then two rows of chroma rounded up to two pairs each
+        let mut data = vec![WHITE; 9];
+        data.extend_from_slice(&[128, 128, 128, 128, 128, 128, 128, 128]);
+        data[4] = BLACK;
+        let frame = nv12_to_rgb(3, 3, &data).unwrap();
+        assert_eq!(frame.get_pixel(1, 1), &Rgb([0, 0, 0]));
+        assert_eq!(frame.get_pixel(2, 2), &Rgb([254, 254, 254]));
+    }
+
+    #[test]
+    fn short_frames_are_errors() {
+        assert!(yuyv_to_rgb(2, 2, &[0; 7]).is_err());
+        assert!(nv12_to_rgb(3, 3, &[0; 16]).is_err());
+    }
+
+    #[test]
+    fn luma_is_taken_as_is() {
+        let yuyv = luma(FrameFormat::YUYV, 2, 1, &[10, 128, 20, 128]).unwrap();
+        assert_eq!(yuyv.into_raw(), vec![10, 20]);
+        let nv12 = luma(FrameFormat::NV12, 2, 2, &[1, 2, 3, 4, 128, 128]).unwrap();
+        assert_eq!(nv12.into_raw(), vec![1, 2, 3, 4]);
+        assert!(luma(FrameFormat::YUYV, 2, 1, &[10, 128]).is_none());
+    }
+}